@@ -1,6 +1,8 @@
 use std::env;
 
 fn main() {
+    // NiFpga_Main.c and NiFpga_Main.lvbitx are next to the header so they are found automatically
+    // and the build reruns when the FPGA is recompiled.
     let mut fpga_c_interface = ni_fpga_interface_build::FpgaCInterface::from_custom_header(
         "../fpga_c_interface/NiFpga_Main.h",
     );
@@ -1,7 +1,12 @@
 //! Holds common definitions shared by the
 //! different C code visitors.
 
-use lang_c::ast::{Constant, Expression, IntegerBase};
+use lang_c::ast::{Constant, Expression, Integer, IntegerBase};
+
+/// The address of a register or FIFO in the C interface.
+///
+/// This matches `ni_fpga_interface::session::Address` so the generator has a single place to change it.
+pub type Address = u32;
 
 /// The type of register value we have found.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
@@ -53,20 +58,134 @@ impl AddressKind {
 }
 
 /// Extract a numeric value from the expression for the enum value.
-pub fn value_from_discriminant(discriminant: &Expression) -> u32 {
+///
+/// As well as integers this handles character constants and the
+/// `{header_prefix}True`/`{header_prefix}False` identifiers which are sometimes used for booleans,
+/// e.g. `NiFpga_True` with the default prefix.
+///
+/// Returns [`None`] if the expression isn't one of these.
+pub fn value_from_discriminant(discriminant: &Expression, header_prefix: &str) -> Option<u32> {
     match &discriminant {
         Expression::Constant(node) => match &node.node {
-            Constant::Integer(value) => {
-                let radix = match value.base {
-                    IntegerBase::Decimal => 10,
-                    IntegerBase::Hexadecimal => 16,
-                    IntegerBase::Octal => 8,
-                    IntegerBase::Binary => 2,
-                };
-                u32::from_str_radix(&value.number, radix).unwrap()
-            }
-            _ => panic!("Unexpected constant type."),
+            Constant::Integer(value) => value_from_integer(value),
+            Constant::Character(value) => value_from_character(value),
+            _ => None,
         },
-        _ => panic!("Unexpected expression type"),
+        Expression::Identifier(identifier) => {
+            match identifier.node.name.strip_prefix(header_prefix)? {
+                "True" => Some(1),
+                "False" => Some(0),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Get the value of a C integer constant in any base.
+pub fn value_from_integer(value: &Integer) -> Option<u32> {
+    let radix = match value.base {
+        IntegerBase::Decimal => 10,
+        IntegerBase::Hexadecimal => 16,
+        IntegerBase::Octal => 8,
+        IntegerBase::Binary => 2,
+    };
+    u32::from_str_radix(&value.number, radix).ok()
+}
+
+/// Get the value of a C character constant such as `'a'` or `'\x01'`.
+fn value_from_character(constant: &str) -> Option<u32> {
+    let inner = constant.strip_prefix('\'')?.strip_suffix('\'')?;
+
+    let Some(escape) = inner.strip_prefix('\\') else {
+        let mut chars = inner.chars();
+        return match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c as u32),
+            _ => None,
+        };
+    };
+
+    match escape {
+        "n" => Some(0x0A),
+        "t" => Some(0x09),
+        "r" => Some(0x0D),
+        "\\" => Some(0x5C),
+        "\'" => Some(0x27),
+        "\"" => Some(0x22),
+        _ => {
+            if let Some(hex) = escape.strip_prefix('x') {
+                u32::from_str_radix(hex, 16).ok()
+            } else {
+                u32::from_str_radix(escape, 8).ok()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lang_c::ast::Identifier;
+    use lang_c::span::{Node, Span};
+
+    fn constant(constant: Constant) -> Expression {
+        Expression::Constant(Box::new(Node::new(constant, Span::none())))
+    }
+
+    #[test]
+    fn test_boolean_identifiers() {
+        let identifier = |name: &str| {
+            Expression::Identifier(Box::new(Node::new(
+                Identifier {
+                    name: name.to_owned(),
+                },
+                Span::none(),
+            )))
+        };
+        assert_eq!(
+            value_from_discriminant(&identifier("NiFpga_True"), "NiFpga_"),
+            Some(1)
+        );
+        assert_eq!(
+            value_from_discriminant(&identifier("NiFpga_False"), "NiFpga_"),
+            Some(0)
+        );
+        assert_eq!(
+            value_from_discriminant(&identifier("Custom_True"), "Custom_"),
+            Some(1)
+        );
+        assert_eq!(
+            value_from_discriminant(&identifier("NiFpga_True"), "Custom_"),
+            None
+        );
+        assert_eq!(
+            value_from_discriminant(&identifier("Unknown"), "NiFpga_"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_character_constants() {
+        let character = |value: &str| constant(Constant::Character(value.to_owned()));
+        assert_eq!(
+            value_from_discriminant(&character("'a'"), "NiFpga_"),
+            Some(97)
+        );
+        assert_eq!(
+            value_from_discriminant(&character("'\\0'"), "NiFpga_"),
+            Some(0)
+        );
+        assert_eq!(
+            value_from_discriminant(&character("'\\1'"), "NiFpga_"),
+            Some(1)
+        );
+        assert_eq!(
+            value_from_discriminant(&character("'\\x1F'"), "NiFpga_"),
+            Some(0x1F)
+        );
+        assert_eq!(
+            value_from_discriminant(&character("'\\n'"), "NiFpga_"),
+            Some(0x0A)
+        );
     }
 }
@@ -1,10 +1,19 @@
 use crate::address_definitions::value_from_discriminant;
 
-use super::address_definitions::AddressKind;
+use super::address_definitions::{Address, AddressKind};
 use lang_c::ast::*;
 use lang_c::span::{Node, Span};
 use lang_c::visit::Visit;
 use std::collections::BTreeMap;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AddressDefinitionsError {
+    #[error("{0} has no value")]
+    MissingValue(String),
+    #[error("Unsupported value for {0}")]
+    UnsupportedValue(String),
+}
 
 /// Defines a register location.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, PartialOrd, Ord)]
@@ -18,28 +27,40 @@ pub struct LocationDefinition {
 ///
 /// We use a BTree here so we get deterministic generation
 /// which was failing tests with HashMap.
-pub type AddressSet = BTreeMap<LocationDefinition, u32>;
+///
+/// The values are the addresses, or the element count for the array size kinds.
+pub type AddressSet = BTreeMap<LocationDefinition, Address>;
 
 /// Extracts the register definitions from the AST.
 pub struct AddressDefinitionsVisitor {
     pub registers: AddressSet,
+    /// The first register whose value couldn't be read. It is left out of the registers.
+    pub error: Option<AddressDefinitionsError>,
+    header_prefix: String,
     prefix: String,
 }
 
 impl AddressDefinitionsVisitor {
-    /// Create the new visitor with the FPGA project prefix.
+    /// Create the new visitor with the header prefix and FPGA project name.
     ///
-    /// e.g. if the file is called `NiFpga_Main.h` then the prefix is Main.
-    pub fn new(interface_name: &str) -> Self {
+    /// e.g. if the file is called `NiFpga_Main.h` then the header prefix is `NiFpga_` and the interface name is Main.
+    pub fn new(header_prefix: &str, interface_name: &str) -> Self {
         Self {
             registers: BTreeMap::new(),
-            prefix: format!("NiFpga_{interface_name}_"),
+            error: None,
+            header_prefix: header_prefix.to_owned(),
+            prefix: format!("{header_prefix}{interface_name}_"),
         }
     }
 
     fn process_enum_type(&mut self, node: &EnumType, type_name: &str) {
-        let enum_name = type_name.strip_prefix(&self.prefix).unwrap();
-        let (kind, type_name) = enum_name_to_types(enum_name);
+        // Enums which aren't for this interface or aren't a register kind aren't registers.
+        let Some((kind, type_name)) = type_name
+            .strip_prefix(&self.prefix)
+            .and_then(enum_name_to_types)
+        else {
+            return;
+        };
 
         for Node { node: variant, .. } in node.enumerators.iter() {
             let ident_string = &variant.identifier.node.name;
@@ -51,26 +72,38 @@ impl AddressDefinitionsVisitor {
                 datatype: type_name.to_owned(),
             };
 
-            let assignment_express = &variant.expression.as_ref().unwrap().node;
-            let value = value_from_discriminant(assignment_express);
+            let value = match &variant.expression {
+                Some(expression) => value_from_discriminant(&expression.node, &self.header_prefix)
+                    .ok_or_else(|| AddressDefinitionsError::UnsupportedValue(ident_string.clone())),
+                None => Err(AddressDefinitionsError::MissingValue(ident_string.clone())),
+            };
 
-            self.registers.insert(definition, value);
+            match value {
+                Ok(value) => {
+                    self.registers.insert(definition, value);
+                }
+                Err(error) => {
+                    self.error.get_or_insert(error);
+                }
+            }
         }
     }
 }
 
 /// Extract the terms for the register kind and return the time
 /// and what is left.
-fn enum_name_to_types(name: &str) -> (AddressKind, &str) {
-    let mut kind = extract_type_from_start(name).unwrap();
-    let mut type_name = name.strip_prefix(kind.prefix()).unwrap();
-
-    if kind.is_array() && name.ends_with("Size") {
-        kind = kind.with_size();
-        type_name = type_name.strip_suffix("Size").unwrap();
+fn enum_name_to_types(name: &str) -> Option<(AddressKind, &str)> {
+    let mut kind = extract_type_from_start(name)?;
+    let mut type_name = name.strip_prefix(kind.prefix())?;
+
+    if kind.is_array() {
+        if let Some(element_type) = type_name.strip_suffix("Size") {
+            kind = kind.with_size();
+            type_name = element_type;
+        }
     }
 
-    (kind, type_name)
+    Some((kind, type_name))
 }
 
 /// Run through the options confirming the prefix.
@@ -95,7 +128,9 @@ fn extract_type_from_start(name: &str) -> Option<AddressKind> {
 /// Extract the name of the individual control which is
 /// the text after the last '_' in the name.
 fn control_indicator_name_from_full(full_name: &str) -> &str {
-    full_name.rsplit_once('_').unwrap().1
+    full_name
+        .rsplit_once('_')
+        .map_or(full_name, |(_, name)| name)
 }
 
 /// Check if the declaration is a typedef.
@@ -145,6 +180,7 @@ impl<'ast> Visit<'ast> for AddressDefinitionsVisitor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::DEFAULT_HEADER_PREFIX;
     use lang_c::driver::{parse_preprocessed, Config};
     use lang_c::visit::Visit;
 
@@ -162,7 +198,7 @@ mod tests {
 
         "#;
 
-        let mut visitor = AddressDefinitionsVisitor::new("Main");
+        let mut visitor = AddressDefinitionsVisitor::new(DEFAULT_HEADER_PREFIX, "Main");
         visit_c_code(content, &mut visitor);
         assert_eq!(visitor.registers.len(), 0);
     }
@@ -176,7 +212,7 @@ mod tests {
         } NiFpga_Main_ControlU8;
         "#;
 
-        let mut visitor = AddressDefinitionsVisitor::new("Main");
+        let mut visitor = AddressDefinitionsVisitor::new(DEFAULT_HEADER_PREFIX, "Main");
         visit_c_code(content, &mut visitor);
 
         let expected = vec![(
@@ -193,6 +229,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_custom_header_prefix() {
+        let content = r#"
+        typedef enum
+        {
+        Custom_Main_ControlU8_U8Control = 0x18002,
+        } Custom_Main_ControlU8;
+        "#;
+
+        let mut visitor = AddressDefinitionsVisitor::new("Custom_", "Main");
+        visit_c_code(content, &mut visitor);
+
+        let key = LocationDefinition {
+            kind: AddressKind::Control,
+            name: "U8Control".to_owned(),
+            datatype: "U8".to_owned(),
+        };
+        assert_eq!(visitor.registers.get(&key).unwrap(), &0x18002);
+    }
+
     #[test]
     fn test_different_interface_name() {
         let content = r#"
@@ -202,7 +258,7 @@ mod tests {
         } NiFpga_If_ControlU8;
         "#;
 
-        let mut visitor = AddressDefinitionsVisitor::new("If");
+        let mut visitor = AddressDefinitionsVisitor::new(DEFAULT_HEADER_PREFIX, "If");
         visit_c_code(content, &mut visitor);
 
         let expected = vec![(
@@ -228,7 +284,7 @@ mod tests {
         } NiFpga_Main_ControlU32;
         "#;
 
-        let mut visitor = AddressDefinitionsVisitor::new("Main");
+        let mut visitor = AddressDefinitionsVisitor::new(DEFAULT_HEADER_PREFIX, "Main");
         visit_c_code(content, &mut visitor);
 
         let expected = vec![(
@@ -255,7 +311,7 @@ mod tests {
             } NiFpga_Main_ControlU8;
         "#;
 
-        let mut visitor = AddressDefinitionsVisitor::new("Main");
+        let mut visitor = AddressDefinitionsVisitor::new(DEFAULT_HEADER_PREFIX, "Main");
         visit_c_code(content, &mut visitor);
 
         let expected = vec![
@@ -291,7 +347,7 @@ mod tests {
         } NiFpga_Main_IndicatorU8;
         "#;
 
-        let mut visitor = AddressDefinitionsVisitor::new("Main");
+        let mut visitor = AddressDefinitionsVisitor::new(DEFAULT_HEADER_PREFIX, "Main");
         visit_c_code(content, &mut visitor);
 
         let expected = vec![(
@@ -324,7 +380,7 @@ mod tests {
             } NiFpga_Main_ControlArrayU8Size;
         "#;
 
-        let mut visitor = AddressDefinitionsVisitor::new("Main");
+        let mut visitor = AddressDefinitionsVisitor::new(DEFAULT_HEADER_PREFIX, "Main");
         visit_c_code(content, &mut visitor);
 
         let expected = vec![
@@ -382,7 +438,7 @@ mod tests {
         } NiFpga_Main_IndicatorArrayU8Size;
         "#;
 
-        let mut visitor = AddressDefinitionsVisitor::new("Main");
+        let mut visitor = AddressDefinitionsVisitor::new(DEFAULT_HEADER_PREFIX, "Main");
         visit_c_code(content, &mut visitor);
 
         let expected = vec![
@@ -419,7 +475,7 @@ mod tests {
         } NiFpga_Main_HostToTargetFifoU8;
         "#;
 
-        let mut visitor = AddressDefinitionsVisitor::new("Main");
+        let mut visitor = AddressDefinitionsVisitor::new(DEFAULT_HEADER_PREFIX, "Main");
         visit_c_code(content, &mut visitor);
 
         let expected = vec![
@@ -456,7 +512,7 @@ mod tests {
         } NiFpga_Main_TargetToHostFifoU16;
         "#;
 
-        let mut visitor = AddressDefinitionsVisitor::new("Main");
+        let mut visitor = AddressDefinitionsVisitor::new(DEFAULT_HEADER_PREFIX, "Main");
         visit_c_code(content, &mut visitor);
 
         let expected = vec![
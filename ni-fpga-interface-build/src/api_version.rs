@@ -0,0 +1,99 @@
+//! Handles the differences between generations of the NI FPGA Interface C API.
+//!
+//! The runtime crate links against the functions defined in `NiFpga.c`. Older generations of the
+//! C API don't define some of these so linking fails with an undefined symbol.
+//!
+//! The generations are told apart by the functions `NiFpga.c` defines rather than by NI-RIO version:
+//!
+//! | Generation | Defines `NiFpga_ConfigureFifo2` | Configure FIFO                               |
+//! |------------|---------------------------------|----------------------------------------------|
+//! | Current    | Yes                             | Called directly and reports the actual depth |
+//! | Legacy     | No                              | Shimmed with `NiFpga_ConfigureFifo`          |
+//!
+//! The other functions the runtime crate wraps are expected in both generations.
+//! There is no shim for `NiFpga_GetPeerToPeerFifoEndpoint` so if your `NiFpga.c` doesn't define it
+//! peer-to-peer FIFOs are unavailable and using them fails to link.
+//!
+//! Check which generation you have by searching the `NiFpga.c` from your interface for the function names.
+//! The build warns if `NiFpga.c` is missing a function which the selected generation needs.
+
+/// The generation of the NI FPGA Interface C API which `NiFpga.c` comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ApiVersion {
+    /// The C API includes all the functions used by this crate.
+    #[default]
+    Current,
+    /// The C API doesn't include `NiFpga_ConfigureFifo2`.
+    ///
+    /// A shim is compiled which implements it with `NiFpga_ConfigureFifo`. As the older
+    /// function doesn't report the actual depth the shim reports zero. `Session::configure_fifo`
+    /// then returns the requested depth and doesn't record it, so the checks which rely on the
+    /// configured depth (e.g. `ReadFifo::buffer_was_full`) are unavailable.
+    Legacy,
+}
+
+/// The functions which are only available in the current generation of the C API.
+const CURRENT_ONLY_FUNCTIONS: &[&str] = &["NiFpga_ConfigureFifo2"];
+
+const CONFIGURE_FIFO2_SHIM: &str = r#"#include "NiFpga.h"
+
+NiFpga_Status NiFpga_ConfigureFifo2(NiFpga_Session session,
+                                    uint32_t       fifo,
+                                    size_t         requestedDepth,
+                                    size_t*        actualDepth)
+{
+   NiFpga_Status status = NiFpga_ConfigureFifo(session, fifo, requestedDepth);
+   if (actualDepth != NULL)
+   {
+      /* Zero tells the Rust side the actual depth is unknown. */
+      *actualDepth = 0;
+   }
+   return status;
+}
+"#;
+
+impl ApiVersion {
+    /// The C source needed to provide the functions missing from this generation, if any.
+    pub(crate) fn shim_source(&self) -> Option<&'static str> {
+        match self {
+            ApiVersion::Current => None,
+            ApiVersion::Legacy => Some(CONFIGURE_FIFO2_SHIM),
+        }
+    }
+
+    /// The functions this generation expects `NiFpga.c` to define which are missing from the source.
+    pub(crate) fn missing_functions(&self, common_c_source: &str) -> Vec<&'static str> {
+        match self {
+            ApiVersion::Current => CURRENT_ONLY_FUNCTIONS
+                .iter()
+                .copied()
+                .filter(|function| !common_c_source.contains(&format!("{function}(")))
+                .collect(),
+            ApiVersion::Legacy => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_reports_missing_configure_fifo2() {
+        let source = "NiFpga_Status NiFpga_ConfigureFifo(NiFpga_Session session)";
+        assert_eq!(
+            ApiVersion::Current.missing_functions(source),
+            vec!["NiFpga_ConfigureFifo2"]
+        );
+        assert!(ApiVersion::Legacy.missing_functions(source).is_empty());
+    }
+
+    #[test]
+    fn test_only_legacy_needs_shim() {
+        assert!(ApiVersion::Current.shim_source().is_none());
+        assert!(ApiVersion::Legacy
+            .shim_source()
+            .unwrap()
+            .contains("NiFpga_ConfigureFifo2"));
+    }
+}
@@ -3,32 +3,45 @@
 //! This is still in rough shape but seems to prove the basic concept.
 
 use super::address_definitions_visitor::AddressDefinitionsVisitor;
-use super::registers_generator::{generate_fifo_module, generate_register_module};
+use super::custom_type_register_visitor::{CustomTypeVisitor, CustomTypeVisitorError, FxpTypeInfo};
+use super::registers_generator::{
+    generate_fifo_module, generate_interface_impl, generate_register_module, GeneratorOptions,
+};
+#[cfg(test)]
+use super::{
+    address_definitions::{Address, AddressKind},
+    address_definitions_visitor::LocationDefinition,
+};
 use super::{
-    address_definitions_visitor::AddressSet, string_constant_visitor::StringConstantVisitor,
+    address_definitions_visitor::AddressSet, numeric_constant_visitor::NumericConstantVisitor,
+    string_constant_visitor::StringConstantVisitor,
 };
+use crate::DEFAULT_HEADER_PREFIX;
 use lang_c::driver::{parse, parse_preprocessed, Config};
 use lang_c::visit::Visit;
 use quote::{quote, ToTokens};
-use std::io::BufReader;
 use std::path::{Path, PathBuf};
+use thiserror::Error;
 
 pub struct InterfaceDescription {
     pub signature: String,
+    /// The `NiFpga_Version` the header was generated for e.g. 190 for NI-RIO 19.0.
+    pub c_api_version: Option<u32>,
     pub registers: AddressSet,
+    /// The FXP controls and indicators by name.
+    ///
+    /// This is an error if the FXP type info couldn't be read, in which case `FXP_INFO` isn't generated.
+    pub fxp_types: Result<Vec<(String, FxpTypeInfo)>, CustomTypeVisitorError>,
 }
 
 impl InterfaceDescription {
     /// Parses the C header file for the specific FPGA interface.
-    pub fn parse_bindings(prefix: &str, content: &Path) -> Self {
-        let new_path = header_to_temp_no_includes(content);
-        let mut config = Config::default();
-        //use cc to find the best compiler.
-        let build = cc::Build::new();
-        config.cpp_command = build.get_compiler().path().to_str().unwrap().to_owned();
-        config.cpp_options = vec!["-E".to_owned()];
-        let file = parse(&config, new_path).unwrap().unit;
-        read_ast(prefix, file)
+    ///
+    /// The header prefix is the text before the interface name on each symbol, normally `NiFpga_`.
+    pub fn parse_bindings(header_prefix: &str, interface_name: &str, content: &Path) -> Self {
+        let file = parse_header(header_prefix, content)
+            .unwrap_or_else(|error| panic!("{}: {error}", content.display()));
+        read_ast(header_prefix, interface_name, file)
     }
 
     /// Parses the pre-processed C header file for the specific FPGA interface.
@@ -40,63 +53,230 @@ impl InterfaceDescription {
     pub fn parse_preprocessed_bindings(prefix: &str, content: String) -> Self {
         let config = Config::with_clang();
         let file = parse_preprocessed(&config, content).unwrap().unit;
-        read_ast(prefix, file)
+        read_ast(DEFAULT_HEADER_PREFIX, prefix, file)
     }
 
     /// Generates a new rust module which contains the interface to the FPGA.
-    pub fn generate_rust_output(&self) -> String {
+    pub fn generate_rust_output(&self, options: &GeneratorOptions) -> String {
         let metadata = self.generate_metadata_output();
-        let registers = generate_register_module(&self.registers);
-        let fifos = generate_fifo_module(&self.registers);
+        let registers = generate_register_module(&self.registers, options);
+        let (fifos, interface) = if options.no_std {
+            (None, None)
+        } else {
+            (
+                Some(generate_fifo_module(&self.registers, options)),
+                Some(generate_interface_impl(&self.registers)),
+            )
+        };
         let tokens = quote! {
             #metadata
+            #interface
             #registers
             #fifos
         };
-        println!("{}", tokens);
         let file = syn::parse2(tokens).unwrap();
         prettyplease::unparse(&file)
     }
 
     fn generate_metadata_output(&self) -> impl ToTokens {
         let signature = &self.signature;
+        let c_api_version = self.c_api_version.map(|version| {
+            let version = proc_macro2::Literal::u32_unsuffixed(version);
+            quote! {
+                #[allow(dead_code)]
+                pub const C_API_VERSION: u32 = #version;
+            }
+        });
+        let fxp_info = self.fxp_types.as_ref().ok().map(|fxp_types| {
+            let fxp_types = fxp_types.iter().map(|(name, info)| {
+                let signed = info.signed;
+                let word_length = proc_macro2::Literal::u8_unsuffixed(info.word_length);
+                let integer_word_length =
+                    proc_macro2::Literal::i16_unsuffixed(info.integer_word_length);
+                quote! {
+                    (#name, ni_fpga_interface::registers::FxpTypeInfo::new(#signed, #word_length, #integer_word_length))
+                }
+            });
+            quote! {
+                #[allow(dead_code)]
+                pub const FXP_INFO: &[(&str, ni_fpga_interface::registers::FxpTypeInfo)] = &[#(#fxp_types),*];
+            }
+        });
         quote! {
             #[allow(dead_code)]
             pub const SIGNATURE: &str = #signature;
+            #c_api_version
+            #fxp_info
+        }
+    }
+}
+
+/// Builds an [`InterfaceDescription`] without having to parse a header.
+///
+/// This keeps tests of the generated code focused on the registers involved.
+#[cfg(test)]
+#[derive(Default)]
+pub struct InterfaceDescriptionBuilder {
+    signature: String,
+    registers: AddressSet,
+}
+
+#[cfg(test)]
+impl InterfaceDescriptionBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn signature(&mut self, signature: &str) -> &mut Self {
+        self.signature = signature.to_owned();
+        self
+    }
+
+    pub fn control(&mut self, name: &str, datatype: &str, address: Address) -> &mut Self {
+        self.add(AddressKind::Control, name, datatype, address)
+    }
+
+    pub fn indicator(&mut self, name: &str, datatype: &str, address: Address) -> &mut Self {
+        self.add(AddressKind::Indicator, name, datatype, address)
+    }
+
+    pub fn array_control(
+        &mut self,
+        name: &str,
+        datatype: &str,
+        address: Address,
+        size: u32,
+    ) -> &mut Self {
+        self.add(AddressKind::ControlArray, name, datatype, address)
+            .add(AddressKind::ControlArraySize, name, datatype, size)
+    }
+
+    pub fn array_indicator(
+        &mut self,
+        name: &str,
+        datatype: &str,
+        address: Address,
+        size: u32,
+    ) -> &mut Self {
+        self.add(AddressKind::IndicatorArray, name, datatype, address)
+            .add(AddressKind::IndicatorArraySize, name, datatype, size)
+    }
+
+    pub fn host_to_target_fifo(
+        &mut self,
+        name: &str,
+        datatype: &str,
+        address: Address,
+    ) -> &mut Self {
+        self.add(AddressKind::HostToTargetFifo, name, datatype, address)
+    }
+
+    pub fn target_to_host_fifo(
+        &mut self,
+        name: &str,
+        datatype: &str,
+        address: Address,
+    ) -> &mut Self {
+        self.add(AddressKind::TargetToHostFifo, name, datatype, address)
+    }
+
+    pub fn build(&self) -> InterfaceDescription {
+        InterfaceDescription {
+            signature: self.signature.clone(),
+            c_api_version: None,
+            registers: self.registers.clone(),
+            fxp_types: Ok(Vec::new()),
         }
     }
+
+    fn add(&mut self, kind: AddressKind, name: &str, datatype: &str, value: Address) -> &mut Self {
+        self.registers.insert(
+            LocationDefinition {
+                kind,
+                name: name.to_owned(),
+                datatype: datatype.to_owned(),
+            },
+            value,
+        );
+        self
+    }
+}
+
+/// The reasons the header couldn't be parsed.
+#[derive(Debug, Error)]
+pub enum HeaderParseError {
+    #[error("Failed to prepare the header for parsing: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to find a C compiler to preprocess the header: {0}")]
+    Compiler(#[from] cc::Error),
+    #[error("Failed to parse the header: {0}")]
+    Parse(#[from] lang_c::driver::Error),
+}
+
+/// Run the header through the C preprocessor and parse it.
+pub fn parse_header(
+    header_prefix: &str,
+    header: &Path,
+) -> Result<lang_c::ast::TranslationUnit, HeaderParseError> {
+    let new_path = header_to_temp_no_includes(header_prefix, header)?;
+    let mut config = Config::default();
+    //use cc to find the best compiler.
+    let build = cc::Build::new();
+    config.cpp_command = build
+        .try_get_compiler()?
+        .path()
+        .to_string_lossy()
+        .into_owned();
+    config.cpp_options = vec!["-E".to_owned()];
+    Ok(parse(&config, new_path)?.unit)
 }
 
 /// Once the AST has been parsed, we can extract the signature and register definitions.
-fn read_ast(prefix: &str, file: lang_c::ast::TranslationUnit) -> InterfaceDescription {
-    let mut sig_visitor = StringConstantVisitor::new(prefix, "Signature");
-    let mut register_visitor = AddressDefinitionsVisitor::new(prefix);
+fn read_ast(
+    header_prefix: &str,
+    interface_name: &str,
+    file: lang_c::ast::TranslationUnit,
+) -> InterfaceDescription {
+    let mut sig_visitor = StringConstantVisitor::new(header_prefix, interface_name, "Signature");
+    let mut version_visitor = NumericConstantVisitor::new(&c_api_version_name(header_prefix));
+    let mut register_visitor = AddressDefinitionsVisitor::new(header_prefix, interface_name);
+    let mut custom_type_visitor = CustomTypeVisitor::new(header_prefix, interface_name);
     sig_visitor.visit_translation_unit(&file);
+    version_visitor.visit_translation_unit(&file);
     register_visitor.visit_translation_unit(&file);
+    custom_type_visitor.visit_translation_unit(&file);
+    if let Some(error) = register_visitor.error {
+        panic!("Failed to read the register definitions: {error}");
+    }
     InterfaceDescription {
         signature: sig_visitor.value.expect("No signature"),
+        c_api_version: version_visitor.value,
         registers: register_visitor.registers,
+        fxp_types: custom_type_visitor.fxp_type_infos(),
     }
 }
 
 /// Cludgy hack to stop pre-processor following headers
 /// which are causing parsing errors. Also we don't need them.
-fn header_to_temp_no_includes(header: &Path) -> PathBuf {
-    use std::fs::{File, OpenOptions};
-    use std::io::BufRead;
+fn header_to_temp_no_includes(header_prefix: &str, header: &Path) -> std::io::Result<PathBuf> {
+    use std::fs::OpenOptions;
     use std::io::Write;
 
     let mut temp = std::env::temp_dir();
-    temp.push(header.file_name().unwrap());
+    temp.push(header.file_name().ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "The header path has no file name",
+        )
+    })?);
 
     let mut output = OpenOptions::new()
         .write(true)
         .create(true)
         .truncate(true)
-        .open(&temp)
-        .unwrap();
+        .open(&temp)?;
 
-    let input = File::open(header).unwrap();
+    let input = std::fs::read_to_string(header)?;
 
     //write in a couple of definitions we will commonly need.
     // making assumptions on short and char for the platform
@@ -118,21 +298,53 @@ typedef struct NiFpga_FxpTypeInfo
 } NiFpga_FxpTypeInfo;
 
 "#;
-    output.write_all(common_types.as_bytes()).unwrap();
+    output.write_all(common_types.as_bytes())?;
+
+    output.write_all(remove_includes(&input).as_bytes())?;
+    output.write_all(c_api_version_declaration(header_prefix).as_bytes())?;
+
+    Ok(temp)
+}
 
-    for line in BufReader::new(input).lines() {
-        let line = line.unwrap();
-        if !line.starts_with("#include") {
-            writeln!(output, "{}", line).unwrap();
+/// The name of the constant we add to the header to capture the C API version.
+fn c_api_version_name(header_prefix: &str) -> String {
+    format!("{header_prefix}CApiVersion")
+}
+
+/// The version is a macro so it is gone after preprocessing.
+/// We declare a constant from it so the value is still in the AST.
+fn c_api_version_declaration(header_prefix: &str) -> String {
+    format!(
+        "\n#ifdef {header_prefix}Version\nstatic const uint32_t {} = {header_prefix}Version;\n#endif\n",
+        c_api_version_name(header_prefix)
+    )
+}
+
+/// Remove the `#include` lines from the header content.
+///
+/// Headers generated on Windows may start with a UTF-8 BOM and use CRLF line endings
+/// so we strip the BOM and normalize the line endings as well.
+/// Otherwise the BOM hides an `#include` on the first line and the preprocessor can miss the first declaration.
+fn remove_includes(header: &str) -> String {
+    let header = header.strip_prefix('\u{feff}').unwrap_or(header);
+    let mut output = String::with_capacity(header.len());
+
+    for line in header.lines() {
+        let line = line.trim_end_matches('\r');
+        if !line.trim_start().starts_with("#include") {
+            output.push_str(line);
+            output.push('\n');
         }
     }
 
-    temp
+    output
 }
 
 #[cfg(test)]
 mod tests {
-    use super::InterfaceDescription;
+    use super::{remove_includes, InterfaceDescription, InterfaceDescriptionBuilder};
+    use crate::registers_generator::GeneratorOptions;
+    use std::path::Path;
 
     #[test]
     fn test_signature_extraction() {
@@ -145,4 +357,248 @@ mod tests {
 
         assert_eq!(description.signature, "E3E0C23C5F01C0DBA61D947AB8A8F489");
     }
+
+    #[test]
+    fn test_signature_extraction_from_bom_crlf_header() {
+        let content = "\u{feff}#include \"NiFpga.h\"\r\nconst char* NiFpga_Main_Signature = \"E3E0C23C5F01C0DBA61D947AB8A8F489\";\r\n";
+
+        let cleaned = remove_includes(content);
+        assert_eq!(
+            cleaned,
+            "const char* NiFpga_Main_Signature = \"E3E0C23C5F01C0DBA61D947AB8A8F489\";\n"
+        );
+
+        let description = InterfaceDescription::parse_preprocessed_bindings("Main", cleaned);
+        assert_eq!(description.signature, "E3E0C23C5F01C0DBA61D947AB8A8F489");
+    }
+
+    #[test]
+    fn test_c_api_version_extraction() {
+        let content = r#"
+        const char* NiFpga_Main_Signature = "E3E0C23C5F01C0DBA61D947AB8A8F489";
+        static const unsigned int NiFpga_CApiVersion = 190;
+        "#;
+
+        let description =
+            InterfaceDescription::parse_preprocessed_bindings("Main", content.to_owned());
+
+        assert_eq!(description.c_api_version, Some(190));
+        assert!(description
+            .generate_rust_output(&GeneratorOptions::default())
+            .contains("pub const C_API_VERSION: u32 = 190;"));
+    }
+
+    #[test]
+    fn test_fxp_info_generated_from_type_info() {
+        let content = r#"
+        typedef unsigned char uint8_t;
+        typedef short int16_t;
+        typedef unsigned int uint32_t;
+        typedef uint8_t NiFpga_Bool;
+        typedef struct NiFpga_FxpTypeInfo
+        {
+            NiFpga_Bool isSigned;
+            uint8_t wordLength;
+            int16_t integerWordLength;
+        } NiFpga_FxpTypeInfo;
+        const char* NiFpga_Main_Signature = "E3E0C23C5F01C0DBA61D947AB8A8F489";
+        const NiFpga_FxpTypeInfo NiFpga_Main_IndicatorFxp_FxpResult_TypeInfo = {1, 33, 17};
+        const uint32_t NiFpga_Main_IndicatorFxp_FxpResult_Resource = 0x1803C;
+        "#;
+
+        let description =
+            InterfaceDescription::parse_preprocessed_bindings("Main", content.to_owned());
+        let output = description.generate_rust_output(&GeneratorOptions::default());
+
+        assert!(output.contains(
+            "(\"FxpResult\", ni_fpga_interface::registers::FxpTypeInfo::new(true, 33, 17))"
+        ));
+    }
+
+    const FXP_TYPE_INFO_HEADER: &str = r#"
+        typedef unsigned char uint8_t;
+        typedef short int16_t;
+        typedef unsigned int uint32_t;
+        typedef uint8_t NiFpga_Bool;
+        typedef struct NiFpga_FxpTypeInfo
+        {
+            NiFpga_Bool isSigned;
+            uint8_t wordLength;
+            int16_t integerWordLength;
+        } NiFpga_FxpTypeInfo;
+        const char* NiFpga_Main_Signature = "E3E0C23C5F01C0DBA61D947AB8A8F489";
+        "#;
+
+    #[test]
+    fn test_fxp_info_for_control_and_indicator_with_same_name() {
+        let content = format!(
+            "{FXP_TYPE_INFO_HEADER}
+            const NiFpga_FxpTypeInfo NiFpga_Main_ControlFxp_Gain_TypeInfo = {{1, 16, -2}};
+            const NiFpga_FxpTypeInfo NiFpga_Main_IndicatorFxp_Gain_TypeInfo = {{0, 8, 4}};"
+        );
+
+        let description = InterfaceDescription::parse_preprocessed_bindings("Main", content);
+        let output = description.generate_rust_output(&GeneratorOptions::default());
+
+        assert!(output
+            .contains("(\"Gain\", ni_fpga_interface::registers::FxpTypeInfo::new(true, 16, -2))"));
+        assert!(output
+            .contains("(\"Gain\", ni_fpga_interface::registers::FxpTypeInfo::new(false, 8, 4))"));
+    }
+
+    #[test]
+    fn test_fxp_info_not_generated_when_type_info_unreadable() {
+        let content = format!(
+            "{FXP_TYPE_INFO_HEADER}
+            const NiFpga_FxpTypeInfo NiFpga_Main_IndicatorFxp_FxpResult_TypeInfo = {{1, 33}};"
+        );
+
+        let description = InterfaceDescription::parse_preprocessed_bindings("Main", content);
+        let output = description.generate_rust_output(&GeneratorOptions::default());
+
+        assert!(description.fxp_types.is_err());
+        assert!(!output.contains("FXP_INFO"));
+        assert!(output.contains("pub const SIGNATURE"));
+    }
+
+    #[test]
+    fn test_builder_output_includes_all_sections() {
+        let description = InterfaceDescriptionBuilder::new()
+            .signature("E3E0C23C5F01C0DBA61D947AB8A8F489")
+            .control("U8Control", "U8", 0x18002)
+            .array_indicator("Results", "I32", 0x18010, 4)
+            .target_to_host_fifo("NumbersFromFPGA", "U16", 0x1)
+            .build();
+
+        let output = description.generate_rust_output(&GeneratorOptions::default());
+
+        assert!(
+            output.contains("pub const SIGNATURE: &str = \"E3E0C23C5F01C0DBA61D947AB8A8F489\";")
+        );
+        assert!(output.contains("pub const U8Control: Register<u8> = Register::new(0x18002);"));
+        assert!(output.contains("pub struct U8ControlReg;"));
+        assert!(output
+            .contains("pub const Results: ArrayRegister<i32, 4> = ArrayRegister::new(0x18010);"));
+        assert!(output.contains("pub const NumbersFromFPGA: ReadFifo<u16> = ReadFifo::new(0x1);"));
+    }
+
+    /// Runs `cargo check` on a crate which includes the generated code in a private module.
+    ///
+    /// Returns the compiler output so the warnings can be checked.
+    fn check_generated_crate(name: &str, generated: &str, main: &str, runtime_std: bool) -> String {
+        let dir = std::env::temp_dir().join(name);
+        let _ = std::fs::remove_dir_all(dir.join("src"));
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        let runtime = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../ni-fpga-interface")
+            .canonicalize()
+            .unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            format!(
+                "[package]\nname = \"{name}\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+                 [dependencies]\nni-fpga-interface = {{ path = {:?}, default-features = {runtime_std} }}\n\n\
+                 [workspace]\n",
+                runtime
+            ),
+        )
+        .unwrap();
+        std::fs::write(dir.join("src/generated.rs"), generated).unwrap();
+        std::fs::write(
+            dir.join("src/main.rs"),
+            format!("mod fpga_defs {{\n    include!(\"generated.rs\");\n}}\n\n{main}\n"),
+        )
+        .unwrap();
+
+        let output = std::process::Command::new(env!("CARGO"))
+            .args(["check", "--offline", "--message-format", "short"])
+            .current_dir(&dir)
+            .env("CARGO_TARGET_DIR", dir.join("target"))
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        assert!(output.status.success(), "{stderr}");
+        stderr
+    }
+
+    #[test]
+    fn test_warn_unused_reports_unused_register() {
+        let description = InterfaceDescriptionBuilder::new()
+            .signature("E3E0C23C5F01C0DBA61D947AB8A8F489")
+            .control("UsedControl", "U8", 0x18002)
+            .indicator("UnusedIndicator", "U8", 0x18006)
+            .host_to_target_fifo("NumbersToFPGA", "U32", 0x0)
+            .build();
+        let options = GeneratorOptions {
+            warn_unused: true,
+            ..Default::default()
+        };
+        let generated = description.generate_rust_output(&options);
+
+        let output = check_generated_crate(
+            "ni_fpga_warn_unused_check",
+            &generated,
+            "fn main() {\n    let _ = fpga_defs::registers::UsedControl;\n    let _ = fpga_defs::fifos::NumbersToFPGA;\n}",
+            true,
+        );
+
+        assert!(
+            output.contains("constant `UnusedIndicator` is never used"),
+            "{output}"
+        );
+        assert!(!output.contains("UsedControl"), "{output}");
+        assert!(!output.contains("NumbersToFPGA"), "{output}");
+        assert!(!output.contains("unused import"), "{output}");
+    }
+
+    #[test]
+    fn test_generated_code_compiles() {
+        let description = InterfaceDescriptionBuilder::new()
+            .signature("E3E0C23C5F01C0DBA61D947AB8A8F489")
+            .control("U8Control", "U8", 0x18002)
+            .array_control("Gains", "Sgl", 0x18010, 4)
+            .indicator("U8Result", "U8", 0x18006)
+            .host_to_target_fifo("NumbersToFPGA", "U32", 0x0)
+            .target_to_host_fifo("NumbersFromFPGA", "U32", 0x1)
+            .build();
+        let generated = description.generate_rust_output(&GeneratorOptions::default());
+
+        check_generated_crate(
+            "ni_fpga_generated_check",
+            &generated,
+            "use fpga_defs::registers::*;\n\n\
+             fn main() {\n    \
+             let _ = (U8ControlReg::ADDRESS, GainsReg::SIZE, address_of(\"U8Result\"));\n    \
+             let _ = apply_defaults::<ni_fpga_interface::session::Session>;\n    \
+             let _ = fpga_defs::fifos::NumbersChannel;\n}",
+            true,
+        );
+    }
+
+    #[test]
+    fn test_no_std_output_compiles_without_runtime_std() {
+        let description = InterfaceDescriptionBuilder::new()
+            .signature("E3E0C23C5F01C0DBA61D947AB8A8F489")
+            .control("U8Control", "U8", 0x18002)
+            .array_control("Gains", "Sgl", 0x18010, 4)
+            .indicator("U8Result", "U8", 0x18006)
+            .host_to_target_fifo("NumbersToFPGA", "U32", 0x0)
+            .build();
+        let options = GeneratorOptions {
+            no_std: true,
+            ..Default::default()
+        };
+        let generated = description.generate_rust_output(&options);
+
+        assert!(!generated.contains("pub mod fifos"));
+        assert!(!generated.contains("FpgaInterface"));
+        check_generated_crate(
+            "ni_fpga_no_std_check",
+            &generated,
+            "use fpga_defs::registers::*;\n\n\
+             fn main() {\n    \
+             let _ = (U8ControlReg::ADDRESS, GainsReg::SIZE, address_of(\"U8Result\"));\n}",
+            false,
+        );
+    }
 }
@@ -25,11 +25,12 @@ use thiserror::Error;
 use lang_c::{
     ast::{
         Declaration, DeclarationSpecifier, DeclaratorKind, Expression, Initializer, TypeQualifier,
+        UnaryOperator,
     },
     visit::Visit,
 };
 
-use crate::address_definitions::value_from_discriminant;
+use crate::address_definitions::{value_from_discriminant, Address};
 
 #[derive(Debug, Error)]
 pub enum CustomTypeVisitorError {
@@ -39,40 +40,67 @@ pub enum CustomTypeVisitorError {
     MissingDeclaratorForInitializer(Declaration),
     #[error("Missing initializer for {0:?}")]
     MissingInitializer(Declaration),
+    #[error("Expected an expression initializer for {0}")]
+    InitializerNotExpression(String),
     #[error("Missing initializer in fxp item")]
     UnexpectedNestingInFxpItem,
     #[error("Insufficient Items in Fxp Initializer")]
     InsufficientItemsInFxpInitializer,
     #[error("Initializer for FXP type data is not a list")]
     FxpInitializerNotList,
+    #[error("Unsupported constant value {0}")]
+    UnsupportedValue(String),
     #[error("Unknown type field {0}")]
     UnknownTypeField(String),
 }
 
+/// Matches the `NiFpga_FxpTypeInfo` struct in the C API.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
-struct FxpTypeInfo {
-    signed: bool,
-    word_length: u32,
-    integer_word_length: u32,
+pub struct FxpTypeInfo {
+    pub signed: bool,
+    pub word_length: u8,
+    /// This can be negative when all the bits are after the binary point.
+    pub integer_word_length: i16,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct FxpRegister {
     name: String,
     fxp_type_info: FxpTypeInfo,
-    address: u32,
+    address: Address,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct ClusterRegister {
     cluster_type: String,
-    address: u32,
+    address: Address,
     packed_size: u32,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
+/// The custom type from the type part of the constant name, e.g. `ControlFxp` or `IndicatorClusterArray`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+enum CustomKind {
+    Fxp,
+    Cluster,
+}
+
+impl CustomKind {
+    fn from_control_type(control_type: &str) -> Option<Self> {
+        let kind = control_type
+            .strip_prefix("Control")
+            .or_else(|| control_type.strip_prefix("Indicator"))?;
+        match kind.strip_suffix("Array").unwrap_or(kind) {
+            "Fxp" => Some(Self::Fxp),
+            "Cluster" => Some(Self::Cluster),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
 struct CustomTypeData {
-    address: Option<u32>,
+    kind: CustomKind,
+    address: Option<Address>,
     fxp_type_info: Option<FxpTypeInfo>,
     cluster_type: Option<String>,
     packed_size: Option<u32>,
@@ -80,78 +108,133 @@ struct CustomTypeData {
 }
 
 pub struct CustomTypeVisitor {
+    header_prefix: String,
     prefix: String,
-    types: HashMap<String, CustomTypeData>,
+    /// Keyed by the type part of the name e.g. `ControlFxp` or `IndicatorClusterArray`, then the control name.
+    ///
+    /// A control and an indicator can share a name so both are needed.
+    types: HashMap<(String, String), CustomTypeData>,
+    /// The first error found while reading the constants.
+    error: Option<CustomTypeVisitorError>,
 }
 
 impl CustomTypeVisitor {
-    pub fn new(interface_name: &str) -> Self {
+    pub fn new(header_prefix: &str, interface_name: &str) -> Self {
         Self {
-            prefix: format!("NiFpga_{}_", interface_name),
+            header_prefix: header_prefix.to_owned(),
+            prefix: format!("{header_prefix}{interface_name}_"),
             types: Default::default(),
+            error: None,
         }
     }
 
+    /// Returns true if any FXP controls or indicators were found.
+    pub fn has_fxp(&self) -> bool {
+        self.types.values().any(|data| data.kind == CustomKind::Fxp)
+    }
+
+    /// Returns true if any cluster controls or indicators were found.
+    pub fn has_clusters(&self) -> bool {
+        self.types
+            .values()
+            .any(|data| data.kind == CustomKind::Cluster)
+    }
+
+    /// The type info of each FXP control and indicator by name, sorted by name.
+    ///
+    /// Returns the first error found while visiting if any of the constants couldn't be read.
+    pub fn fxp_type_infos(self) -> Result<Vec<(String, FxpTypeInfo)>, CustomTypeVisitorError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+        let mut infos: Vec<(String, String, FxpTypeInfo)> = self
+            .types
+            .into_iter()
+            .filter_map(|((control_type, name), data)| {
+                data.fxp_type_info
+                    .map(|type_info| (name, control_type, type_info))
+            })
+            .collect();
+        infos.sort_by(|(a, a_type, _), (b, b_type, _)| (a, a_type).cmp(&(b, b_type)));
+        Ok(infos
+            .into_iter()
+            .map(|(name, _, type_info)| (name, type_info))
+            .collect())
+    }
+
     pub fn get_registers(
         &self,
     ) -> Result<(Vec<FxpRegister>, Vec<ClusterRegister>), CustomTypeVisitorError> {
         let mut fxp_regs = Vec::new();
-        for value in &self.types {
+        for ((_, name), data) in &self.types {
+            let Some(fxp_type_info) = data.fxp_type_info.clone() else {
+                continue;
+            };
             fxp_regs.push(FxpRegister {
-                name: value.0.clone(),
-                fxp_type_info: value
-                    .1
-                    .fxp_type_info
-                    .clone()
-                    .expect("Should have FXP type info"),
-                address: value
-                    .1
+                name: name.clone(),
+                fxp_type_info,
+                address: data
                     .address
-                    .ok_or(CustomTypeVisitorError::MissingAddress(value.0.clone()))?,
+                    .ok_or(CustomTypeVisitorError::MissingAddress(name.clone()))?,
             });
         }
 
         Ok((fxp_regs, Vec::new()))
     }
+
+    fn read_constant(&mut self, declaration: &Declaration) -> Result<(), CustomTypeVisitorError> {
+        let Some(name) = get_constant_name(declaration) else {
+            return Ok(());
+        };
+        let Some(reg_details) = get_constant_type_from_name(&self.prefix, name) else {
+            return Ok(());
+        };
+        let Some(kind) = CustomKind::from_control_type(reg_details.control_type) else {
+            return Ok(());
+        };
+
+        let control_definition = self
+            .types
+            .entry((
+                reg_details.control_type.to_owned(),
+                reg_details.control_name.to_owned(),
+            ))
+            .or_insert(CustomTypeData {
+                kind,
+                address: None,
+                fxp_type_info: None,
+                cluster_type: None,
+                packed_size: None,
+                array_size: None,
+            });
+
+        match reg_details.suffix {
+            "Resource" => {
+                control_definition.address =
+                    Some(read_init_value_number(declaration, &self.header_prefix)?);
+            }
+            "TypeInfo" => {
+                control_definition.fxp_type_info =
+                    Some(read_init_fixed_type(declaration, &self.header_prefix)?);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
 }
 
 impl<'ast> Visit<'ast> for CustomTypeVisitor {
     fn visit_declaration(
         &mut self,
         declaration: &'ast lang_c::ast::Declaration,
-        span: &'ast lang_c::span::Span,
+        _span: &'ast lang_c::span::Span,
     ) {
         if !is_constant_definition(declaration) {
             return;
         }
 
-        let name = get_constant_name(declaration);
-        println!("Found constant {}", name);
-
-        if let Some(reg_details) = get_constant_type_from_name(&self.prefix, name) {
-            println!("Found {:?}", reg_details);
-            if !self.types.contains_key(reg_details.control_name) {
-                self.types
-                    .insert(reg_details.control_name.to_owned(), Default::default());
-            }
-
-            let control_definition = self
-                .types
-                .get_mut(reg_details.control_name)
-                .expect("Last step should have created this");
-
-            match reg_details.suffix {
-                "Resource" => {
-                    let value =
-                        read_init_value_number(declaration).expect("Should be a register value");
-                    control_definition.address = Some(value);
-                }
-                "TypeInfo" => {
-                    control_definition.fxp_type_info =
-                        Some(read_init_fixed_type(declaration).expect("FXP type"));
-                }
-                _ => {}
-            }
+        if let Err(error) = self.read_constant(declaration) {
+            self.error.get_or_insert(error);
         }
     }
 }
@@ -179,7 +262,7 @@ fn get_init_declarators(
         .map(|init_declarator| &init_declarator.node)
 }
 
-fn get_constant_name(declaration: &lang_c::ast::Declaration) -> &str {
+fn get_constant_name(declaration: &lang_c::ast::Declaration) -> Option<&str> {
     let init_declarators = get_init_declarators(declaration);
     let mut names = init_declarators.filter_map(|init_declarator| {
         match &init_declarator.declarator.node.kind.node {
@@ -187,7 +270,7 @@ fn get_constant_name(declaration: &lang_c::ast::Declaration) -> &str {
             _ => None,
         }
     });
-    names.next().expect("Must be at least one identifier")
+    names.next()
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -228,7 +311,6 @@ fn get_initializer(
         .initializer
         .as_ref()
         .ok_or_else(|| CustomTypeVisitorError::MissingInitializer(declaration.clone()))?;
-    println!("Found initializer {:?}", initializer.node);
     Ok(&initializer.node)
 }
 
@@ -238,28 +320,52 @@ fn get_constant_express(
     let initializer = get_initializer(declaration)?;
     match initializer {
         lang_c::ast::Initializer::Expression(expression) => Ok(&expression.as_ref().node),
-        _ => panic!("Should be a expression initializer"),
+        _ => Err(CustomTypeVisitorError::InitializerNotExpression(
+            get_constant_name(declaration)
+                .unwrap_or_default()
+                .to_owned(),
+        )),
     }
 }
 
 fn read_init_value_number(
     declaration: &lang_c::ast::Declaration,
+    header_prefix: &str,
 ) -> Result<u32, CustomTypeVisitorError> {
     let expression = get_constant_express(declaration)?;
-    Ok(value_from_discriminant(expression))
+    value_from_discriminant(expression, header_prefix)
+        .ok_or_else(|| CustomTypeVisitorError::UnsupportedValue(format!("{expression:?}")))
+}
+
+/// Read a value which may be negated, like the integer word length in `{1, 16, -2}`.
+fn read_signed_value(
+    expression: &Expression,
+    header_prefix: &str,
+) -> Result<i64, CustomTypeVisitorError> {
+    match expression {
+        Expression::UnaryOperator(unary) if unary.node.operator.node == UnaryOperator::Minus => {
+            Ok(-read_signed_value(&unary.node.operand.node, header_prefix)?)
+        }
+        _ => value_from_discriminant(expression, header_prefix)
+            .map(i64::from)
+            .ok_or_else(|| CustomTypeVisitorError::UnsupportedValue(format!("{expression:?}"))),
+    }
 }
 
-fn read_init_fixed_type(declaration: &Declaration) -> Result<FxpTypeInfo, CustomTypeVisitorError> {
+fn read_init_fixed_type(
+    declaration: &Declaration,
+    header_prefix: &str,
+) -> Result<FxpTypeInfo, CustomTypeVisitorError> {
     let initializer = get_initializer(declaration)?;
     match initializer {
         Initializer::List(items) => {
-            let values: Result<Vec<u32>, CustomTypeVisitorError> = items
+            let values: Result<Vec<i64>, CustomTypeVisitorError> = items
                 .iter()
                 .map(|item| {
                     let item_initializer = &item.node.initializer.as_ref().node;
                     match item_initializer {
                         Initializer::Expression(expression) => {
-                            Ok(value_from_discriminant(&expression.node))
+                            read_signed_value(&expression.node, header_prefix)
                         }
                         _ => Err(CustomTypeVisitorError::UnexpectedNestingInFxpItem),
                     }
@@ -272,10 +378,12 @@ fn read_init_fixed_type(declaration: &Declaration) -> Result<FxpTypeInfo, Custom
                 return Err(CustomTypeVisitorError::InsufficientItemsInFxpInitializer);
             }
 
+            let out_of_range =
+                |value: i64| CustomTypeVisitorError::UnsupportedValue(value.to_string());
             Ok(FxpTypeInfo {
                 signed: values[0] != 0,
-                word_length: values[1],
-                integer_word_length: values[2],
+                word_length: values[1].try_into().map_err(|_| out_of_range(values[1]))?,
+                integer_word_length: values[2].try_into().map_err(|_| out_of_range(values[2]))?,
             })
         }
         _ => Err(CustomTypeVisitorError::FxpInitializerNotList),
@@ -286,6 +394,7 @@ fn read_init_fixed_type(declaration: &Declaration) -> Result<FxpTypeInfo, Custom
 mod tests {
 
     use super::*;
+    use crate::DEFAULT_HEADER_PREFIX;
     use lang_c::driver::{parse_preprocessed, Config};
     use lang_c::visit::Visit;
 
@@ -323,6 +432,91 @@ mod tests {
         assert!(constant_types.is_none());
     }
 
+    #[test]
+    fn test_fxp_type_info_with_boolean_identifier() {
+        let content = r#"
+typedef unsigned char uint8_t;
+typedef short int16_t;
+typedef uint8_t NiFpga_Bool;
+
+typedef struct NiFpga_FxpTypeInfo
+{
+    NiFpga_Bool isSigned;
+    uint8_t wordLength;
+    int16_t integerWordLength;
+} NiFpga_FxpTypeInfo;
+
+const NiFpga_FxpTypeInfo NiFpga_Main_IndicatorFxp_FxpResult_TypeInfo = {NiFpga_True,33,17};
+        "#;
+        let config = Config::default();
+        let file = parse_preprocessed(&config, content.to_owned()).unwrap();
+        let Some(lang_c::ast::ExternalDeclaration::Declaration(declaration)) =
+            file.unit.0.last().map(|node| &node.node)
+        else {
+            panic!("Expected a declaration");
+        };
+
+        let type_info = read_init_fixed_type(&declaration.node, DEFAULT_HEADER_PREFIX).unwrap();
+
+        assert_eq!(
+            type_info,
+            FxpTypeInfo {
+                signed: true,
+                word_length: 33,
+                integer_word_length: 17,
+            }
+        );
+    }
+
+    #[test]
+    fn test_fxp_type_info_with_negative_integer_word_length() {
+        let content = r#"
+typedef unsigned char uint8_t;
+typedef short int16_t;
+typedef uint8_t NiFpga_Bool;
+
+typedef struct NiFpga_FxpTypeInfo
+{
+    NiFpga_Bool isSigned;
+    uint8_t wordLength;
+    int16_t integerWordLength;
+} NiFpga_FxpTypeInfo;
+
+const NiFpga_FxpTypeInfo NiFpga_Main_ControlFxp_Small_TypeInfo = {1,16,-2};
+        "#;
+
+        let mut visitor = CustomTypeVisitor::new(DEFAULT_HEADER_PREFIX, "Main");
+        visit_c_code(content, &mut visitor);
+
+        assert_eq!(
+            visitor.fxp_type_infos().unwrap(),
+            vec![(
+                "Small".to_owned(),
+                FxpTypeInfo {
+                    signed: true,
+                    word_length: 16,
+                    integer_word_length: -2,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_resource_value_is_an_error() {
+        let content = r#"
+typedef unsigned int uint32_t;
+const uint32_t NiFpga_Main_IndicatorFxp_FxpResult_Resource = 1.5;
+        "#;
+
+        let mut visitor = CustomTypeVisitor::new(DEFAULT_HEADER_PREFIX, "Main");
+        visit_c_code(content, &mut visitor);
+
+        assert!(matches!(
+            visitor.fxp_type_infos(),
+            Err(CustomTypeVisitorError::UnsupportedValue(_))
+        ));
+    }
+
     #[ignore = "still brittle and unfinished"]
     #[test]
     fn test_fxp_control_and_indicator() {
@@ -356,7 +550,7 @@ const NiFpga_FxpTypeInfo NiFpga_Main_ControlFxp_FxpSum_TypeInfo =
 const uint32_t NiFpga_Main_ControlFxp_FxpSum_Resource = 0x18040;
         "#;
 
-        let mut visitor = CustomTypeVisitor::new("Main");
+        let mut visitor = CustomTypeVisitor::new(DEFAULT_HEADER_PREFIX, "Main");
         visit_c_code(content, &mut visitor);
 
         let (fxp_regs, _) = visitor.get_registers().unwrap();
@@ -0,0 +1,140 @@
+//! Reports which features an FPGA interface uses before it is built.
+//!
+//! FXP and cluster types need the interface specific `.c` file
+//! which is easy to miss when setting up a new project.
+
+use crate::address_definitions::AddressKind;
+use crate::address_definitions_visitor::AddressDefinitionsVisitor;
+use crate::custom_type_register_visitor::CustomTypeVisitor;
+use lang_c::ast::TranslationUnit;
+use lang_c::visit::Visit;
+
+/// The features found in the FPGA interface header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct InterfaceAnalysis {
+    /// The interface has FXP controls or indicators.
+    pub has_fxp: bool,
+    /// The interface has cluster controls or indicators.
+    pub has_clusters: bool,
+    /// The interface has array controls or indicators.
+    pub has_arrays: bool,
+    /// The interface has DMA FIFOs.
+    pub has_fifos: bool,
+    /// The interface specific `.c` file was found next to the header.
+    pub custom_c_found: bool,
+}
+
+impl InterfaceAnalysis {
+    /// The interface uses types which need the interface specific `.c` file.
+    pub fn requires_custom_c(&self) -> bool {
+        self.has_fxp || self.has_clusters
+    }
+
+    /// The interface needs the interface specific `.c` file but it wasn't found.
+    pub fn is_missing_custom_c(&self) -> bool {
+        self.requires_custom_c() && !self.custom_c_found
+    }
+}
+
+/// Analyze the parsed header for the interface.
+pub fn analyze_translation_unit(
+    header_prefix: &str,
+    interface_name: &str,
+    file: &TranslationUnit,
+    custom_c_found: bool,
+) -> InterfaceAnalysis {
+    let mut custom_type_visitor = CustomTypeVisitor::new(header_prefix, interface_name);
+    let mut register_visitor = AddressDefinitionsVisitor::new(header_prefix, interface_name);
+    custom_type_visitor.visit_translation_unit(file);
+    register_visitor.visit_translation_unit(file);
+
+    let kinds = || register_visitor.registers.keys().map(|def| def.kind);
+
+    InterfaceAnalysis {
+        has_fxp: custom_type_visitor.has_fxp(),
+        has_clusters: custom_type_visitor.has_clusters(),
+        has_arrays: kinds().any(|kind| kind.is_array()),
+        has_fifos: kinds().any(|kind| {
+            matches!(
+                kind,
+                AddressKind::HostToTargetFifo | AddressKind::TargetToHostFifo
+            )
+        }),
+        custom_c_found,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DEFAULT_HEADER_PREFIX;
+    use lang_c::driver::{parse_preprocessed, Config};
+
+    fn analyze(content: &str, custom_c_found: bool) -> InterfaceAnalysis {
+        let config = Config::default();
+        let file = parse_preprocessed(&config, content.to_owned()).unwrap();
+        analyze_translation_unit(DEFAULT_HEADER_PREFIX, "Main", &file.unit, custom_c_found)
+    }
+
+    #[test]
+    fn test_basic_interface() {
+        let content = r#"
+        typedef enum
+        {
+        NiFpga_Main_ControlU8_U8Control = 0x18002,
+        } NiFpga_Main_ControlU8;
+        typedef enum
+        {
+        NiFpga_Main_TargetToHostFifoU16_NumbersFromFPGA = 1,
+        } NiFpga_Main_TargetToHostFifoU16;
+        "#;
+
+        let analysis = analyze(content, false);
+
+        assert_eq!(
+            analysis,
+            InterfaceAnalysis {
+                has_fifos: true,
+                ..Default::default()
+            }
+        );
+        assert!(!analysis.is_missing_custom_c());
+    }
+
+    #[test]
+    fn test_cluster_without_custom_c() {
+        let content = r#"
+        typedef unsigned int uint32_t;
+        const uint32_t NiFpga_Main_ControlClusterArray_ClusterControl2_Resource = 0x18054;
+        const uint32_t NiFpga_Main_ControlClusterArray_ClusterControl2_Size = 2;
+        "#;
+
+        let analysis = analyze(content, false);
+
+        assert!(analysis.has_clusters);
+        assert!(!analysis.has_fxp);
+        assert!(analysis.is_missing_custom_c());
+    }
+
+    #[test]
+    fn test_fxp_array_and_unrelated_enum() {
+        let content = r#"
+        typedef unsigned int uint32_t;
+        typedef enum
+        {
+        NiFpga_Other_Status_Ready = 1,
+        } NiFpga_Other_Status;
+        typedef enum
+        {
+        NiFpga_Main_ControlFxp_Unsupported = 0x1.0p0,
+        } NiFpga_Main_ControlFxp;
+        const uint32_t NiFpga_Main_IndicatorFxpArray_FxpResults_Resource = 0x18048;
+        "#;
+
+        let analysis = analyze(content, true);
+
+        assert!(analysis.has_fxp);
+        assert!(!analysis.has_clusters);
+        assert!(!analysis.is_missing_custom_c());
+    }
+}
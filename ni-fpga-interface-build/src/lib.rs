@@ -16,32 +16,75 @@
 //!
 //! This will contain a module with the definitions of registers and DMA FIFOs.
 //!
+//! Each register is a const of the typed register. Alongside it is a unit type
+//! named `{Name}Reg` which exposes the address and kind as associated constants
+//! and dereferences to the register so it can be read and written directly.
+//!
 //! # Example Output
 //!
 //! ```rust,ignore
 //! pub const SIGNATURE: &str = "A0613989B20F45FC6E79EB71383493E8";
+//! pub const C_API_VERSION: u32 = 190;
+//! pub const FXP_INFO: &[(&str, ni_fpga_interface::registers::FxpTypeInfo)] = &[
+//!     ("FxpResult", ni_fpga_interface::registers::FxpTypeInfo::new(true, 33, 17)),
+//! ];
 //!
 //! pub mod registers {
-//!     use ni_fpga_interface::registers::{ArrayRegister, Register};
-//!     pub const SglSumArray: ArrayRegister<f32, 4> = ArrayRegister::new(0x1801C);
-//!     pub const U8ControlArray: ArrayRegister<u8, 4> = ArrayRegister::new(0x18014);
-//!     pub const U8SumArray: ArrayRegister<u8, 4> = ArrayRegister::new(0x18010);
-//!     pub const SglControl: Register<f32> = Register::new(0x1802C);
-//!     pub const U8Sum: Register<u8> = Register::new(0x18006);
+//!     use ni_fpga_interface::registers::{ArrayRegister, Register, RegisterKind};
+//!     use ni_fpga_interface::session::RegisterAddress;
+//!
 //!     pub const U8Control: Register<u8> = Register::new(0x18002);
-//!     pub const SglSum: Register<f32> = Register::new(0x18028);
-//!     pub const SglResult: Register<f32> = Register::new(0x18024);
+//!     #[derive(Clone, Copy)]
+//!     pub struct U8ControlReg;
+//!
+//!     impl U8ControlReg {
+//!         pub const ADDRESS: RegisterAddress = 0x18002;
+//!         pub const KIND: RegisterKind = RegisterKind::Control;
+//!     }
+//!
+//!     impl core::ops::Deref for U8ControlReg {
+//!         type Target = Register<u8>;
+//!         fn deref(&self) -> &Self::Target {
+//!             &U8Control
+//!         }
+//!     }
+//!
 //!     pub const SglResultArray: ArrayRegister<f32, 4> = ArrayRegister::new(0x18018);
-//!     pub const IRQs: Register<u32> = Register::new(0x18060);
-//!     pub const U8Result: Register<u8> = Register::new(0x1800A);
-//!     pub const U8ResultArray: ArrayRegister<u8, 4> = ArrayRegister::new(0x1800C);
-//!     pub const SglControlArray: ArrayRegister<f32, 4> = ArrayRegister::new(0x18020);
+//!     #[derive(Clone, Copy)]
+//!     pub struct SglResultArrayReg;
+//!
+//!     impl SglResultArrayReg {
+//!         pub const ADDRESS: RegisterAddress = 0x18018;
+//!         pub const KIND: RegisterKind = RegisterKind::Indicator;
+//!         pub const SIZE: usize = 4;
+//!     }
+//!
+//!     impl core::ops::Deref for SglResultArrayReg {
+//!         type Target = ArrayRegister<f32, 4>;
+//!         fn deref(&self) -> &Self::Target {
+//!             &SglResultArray
+//!         }
+//!     }
+//!
+//!     // ... and the same for every other control and indicator.
 //! }
 //!
 //! pub mod fifos {
 //!     use ni_fpga_interface::fifos::{ReadFifo, WriteFifo};
 //!     pub const NumbersFromFPGA: ReadFifo<u16> = ReadFifo::new(0x1);
 //!     pub const NumbersToFPGA: WriteFifo<u32> = WriteFifo::new(0x0);
+//!     pub const NumbersChannel: ni_fpga_interface::fifos::FifoChannel<u32, u16> =
+//!         ni_fpga_interface::fifos::FifoChannel::new(NumbersToFPGA, NumbersFromFPGA);
+//!
+//!     pub const ALL_FIFOS: &[(&str, ni_fpga_interface::session::FifoAddress)] = &[("NumbersFromFPGA", 0x1), ("NumbersToFPGA", 0x0)];
+//!
+//!     // Configures and starts every FIFO in `ALL_FIFOS`.
+//!     pub fn configure_all(
+//!         session: &impl ni_fpga_interface::session::FifoControlInterface,
+//!         plan: &ni_fpga_interface::fifos::FifoPlan,
+//!     ) -> Result<ni_fpga_interface::fifos::FifoDepths, ni_fpga_interface::fifos::FifoPlanError> {
+//!         ni_fpga_interface::fifos::configure_all(session, ALL_FIFOS, plan)
+//!     }
 //! }
 //! ```
 //!
@@ -55,23 +98,45 @@
 
 mod address_definitions;
 mod address_definitions_visitor;
+mod api_version;
 mod bindings_parser;
 mod custom_type_register_visitor;
+mod interface_analysis;
+mod numeric_constant_visitor;
+mod python_stub_generator;
 mod registers_generator;
 mod string_constant_visitor;
 
+pub use api_version::ApiVersion;
+pub use bindings_parser::HeaderParseError;
+pub use interface_analysis::InterfaceAnalysis;
+use registers_generator::GeneratorOptions;
 use std::{
     env,
     path::{Path, PathBuf},
 };
 
+/// The prefix NI uses for the generated C API files and symbols.
+const DEFAULT_HEADER_PREFIX: &str = "NiFpga_";
+
+/// The file in `OUT_DIR` which records that the common `NiFpga.c` has been compiled.
+const COMMON_LIB_MARKER: &str = "ni_fpga_common.built";
+
 /// Defines the generated C interface for the FPGA project.
 pub struct FpgaCInterface {
     common_c: PathBuf,
     custom_h: PathBuf,
     custom_c: Option<PathBuf>,
     interface_name: String,
+    header_prefix: String,
     sysroot: Option<String>,
+    format_with_rustfmt: bool,
+    rustfmt_edition: Option<String>,
+    python_stub: Option<PathBuf>,
+    bitfile: Option<PathBuf>,
+    shared_common: bool,
+    api_version: ApiVersion,
+    generator_options: GeneratorOptions,
 }
 
 impl FpgaCInterface {
@@ -81,37 +146,55 @@ impl FpgaCInterface {
     /// e.g. NiFpga_prefix.h not NiFpga.h.
     ///
     /// This finds the other files assuming they are in the same folder.
+    ///
+    /// If the header doesn't use the `NiFpga_` prefix then set the prefix with [`FpgaCInterface::header_prefix`].
     pub fn from_custom_header(fpga_header: impl AsRef<Path>) -> Self {
         let fpga_header = fpga_header.as_ref();
         let fpga_header = fpga_header.to_owned();
         let interface_folder = fpga_header.parent().unwrap();
-        let interface_name = fpga_header
-            .file_stem()
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .strip_prefix("NiFpga_")
-            .unwrap()
-            .to_owned();
-
         let common_c = interface_folder.join("NiFpga.c");
-        let custom_c = interface_folder.join(format!("NiFpga_{}.c", interface_name));
 
-        let custom_c = if custom_c.exists() {
-            Some(custom_c)
-        } else {
-            None
-        };
+        let (interface_name, custom_c) = find_interface_files(&fpga_header, DEFAULT_HEADER_PREFIX);
+        let bitfile = find_bitfile(&fpga_header);
 
         Self {
             common_c,
             custom_h: fpga_header,
             custom_c,
             interface_name,
+            header_prefix: DEFAULT_HEADER_PREFIX.to_owned(),
             sysroot: None,
+            format_with_rustfmt: false,
+            rustfmt_edition: None,
+            python_stub: None,
+            bitfile,
+            shared_common: false,
+            api_version: ApiVersion::default(),
+            generator_options: GeneratorOptions::default(),
         }
     }
 
+    /// Sets the prefix used by the generated header instead of `NiFpga_`.
+    ///
+    /// This is used to find the interface name from the header file name, to find the matching `.c` file
+    /// and for the symbols in the header. e.g. `Custom_Main.h` with `Custom_` as the prefix has an
+    /// interface name of `Main` with symbols like `Custom_Main_Signature`.
+    ///
+    /// The generated module is named with the same prefix e.g. `Custom_Main.rs`.
+    /// ```no_run
+    /// use ni_fpga_interface_build::FpgaCInterface;
+    /// FpgaCInterface::from_custom_header("Custom_Main.h")
+    ///    .header_prefix("Custom_")
+    ///   .build();
+    /// ```
+    pub fn header_prefix(&mut self, prefix: impl Into<String>) -> &mut Self {
+        self.header_prefix = prefix.into();
+        let (interface_name, custom_c) = find_interface_files(&self.custom_h, &self.header_prefix);
+        self.interface_name = interface_name;
+        self.custom_c = custom_c;
+        self
+    }
+
     /// Sets the sysroot for the C compiler.
     /// This is useful for cross compiling.
     /// ```no_run
@@ -125,48 +208,460 @@ impl FpgaCInterface {
         self
     }
 
+    /// Format the generated module with `rustfmt` instead of the default prettyplease style.
+    ///
+    /// This is useful if you commit the generated file and check it with `rustfmt --check`
+    /// as `rustfmt` will pick up the configuration of the crate being built.
+    ///
+    /// The `RUSTFMT` environment variable can be used to specify the `rustfmt` binary.
+    /// If `rustfmt` can't be run we fall back to the default formatting with a cargo warning.
+    /// ```no_run
+    /// use ni_fpga_interface_build::FpgaCInterface;
+    /// FpgaCInterface::from_custom_header("NiFpga_prefix.h")
+    ///    .format_with_rustfmt(true)
+    ///   .build();
+    /// ```
+    pub fn format_with_rustfmt(&mut self, enable: bool) -> &mut Self {
+        self.format_with_rustfmt = enable;
+        self
+    }
+
+    /// Set the edition `rustfmt` formats the generated module with, e.g. `"2021"`.
+    ///
+    /// By default no edition is passed so `rustfmt` takes it from the `rustfmt.toml` of the crate being built
+    /// or uses its own default. Set this to the edition of your crate if it has no `rustfmt.toml`.
+    /// This has no effect unless [`FpgaCInterface::format_with_rustfmt`] is enabled.
+    /// ```no_run
+    /// use ni_fpga_interface_build::FpgaCInterface;
+    /// FpgaCInterface::from_custom_header("NiFpga_prefix.h")
+    ///    .format_with_rustfmt(true)
+    ///    .rustfmt_edition("2021")
+    ///   .build();
+    /// ```
+    pub fn rustfmt_edition(&mut self, edition: impl Into<String>) -> &mut Self {
+        self.rustfmt_edition = Some(edition.into());
+        self
+    }
+
+    /// Also write a Python module describing the interface to the given path.
+    ///
+    /// This contains the signature and the names, kinds, types and addresses of the registers and FIFOs
+    /// for use with the NI C API through ctypes. As it is generated from the same header as the Rust
+    /// module the two can't drift out of sync.
+    /// ```no_run
+    /// use ni_fpga_interface_build::FpgaCInterface;
+    /// FpgaCInterface::from_custom_header("NiFpga_prefix.h")
+    ///    .emit_python_stub("../python/fpga_interface.py")
+    ///   .build();
+    /// ```
+    pub fn emit_python_stub(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.python_stub = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Set the path of the `.lvbitx` bitfile the interface was generated from.
+    ///
+    /// The build script will then rerun when the bitfile changes, as well as when the C files do,
+    /// so the generated interface doesn't go stale after the FPGA is recompiled.
+    ///
+    /// A bitfile next to the header with the same name, e.g. `NiFpga_prefix.lvbitx`, is found automatically
+    /// so this is only needed if it is somewhere else.
+    /// ```no_run
+    /// use ni_fpga_interface_build::FpgaCInterface;
+    /// FpgaCInterface::from_custom_header("NiFpga_prefix.h")
+    ///    .bitfile("../fpga/NiFpga_prefix.lvbitx")
+    ///   .build();
+    /// ```
+    pub fn bitfile(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.bitfile = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Compile the common `NiFpga.c` into a library shared by every interface in the build script.
+    ///
+    /// Without this the first interface built compiles `NiFpga.c` and its own `.c` file together into `ni_fpga`.
+    /// With it the first interface built compiles `NiFpga.c` on its own into `ni_fpga_common`.
+    /// In both cases any later interfaces only compile their own `.c` file into `ni_fpga_{interface name}`.
+    ///
+    /// `NiFpga.c` is only compiled once per run of the build script whichever mode each interface uses,
+    /// so interfaces with and without this set can be mixed. This is tracked by a marker file in `OUT_DIR`
+    /// rather than in memory so it also holds if the build script links more than one version of this crate.
+    /// ```no_run
+    /// use ni_fpga_interface_build::FpgaCInterface;
+    /// FpgaCInterface::from_custom_header("NiFpga_Main.h")
+    ///    .shared_common(true)
+    ///   .build();
+    /// FpgaCInterface::from_custom_header("NiFpga_Secondary.h")
+    ///    .shared_common(true)
+    ///   .build();
+    /// ```
+    pub fn shared_common(&mut self, enable: bool) -> &mut Self {
+        self.shared_common = enable;
+        self
+    }
+
+    /// Set the generation of the NI FPGA Interface C API that `NiFpga.c` comes from.
+    ///
+    /// Older generations don't define every function the runtime crate uses which causes link errors.
+    /// Selecting [`ApiVersion::Legacy`] compiles a shim for the missing functions.
+    /// See [`ApiVersion`] for the functions affected.
+    /// ```no_run
+    /// use ni_fpga_interface_build::{ApiVersion, FpgaCInterface};
+    /// FpgaCInterface::from_custom_header("NiFpga_prefix.h")
+    ///    .api_version(ApiVersion::Legacy)
+    ///   .build();
+    /// ```
+    pub fn api_version(&mut self, api_version: ApiVersion) -> &mut Self {
+        self.api_version = api_version;
+        self
+    }
+
+    /// Use a Rust enum for a control or indicator which is a LabVIEW enum.
+    ///
+    /// The C API exports enums as their underlying integer type without the
+    /// variant names, so you provide the enum and we generate an
+    /// `EnumRegister` for it instead of a plain register.
+    /// Nothing about the variants is read from the header so it is up to you to keep
+    /// the names and values in step with the LabVIEW enum.
+    ///
+    /// The enum must implement `TryFrom` for the integer type to read it and `Into` the integer type to write it.
+    /// An undefined value read from the FPGA returns an error containing the raw value.
+    /// Enums used for controls must also implement `Clone` to be written from the generated `Defaults`.
+    ///
+    /// The type must be a path that can be resolved from the generated module, e.g. `crate::MachineState`.
+    /// ```no_run
+    /// use ni_fpga_interface_build::FpgaCInterface;
+    /// FpgaCInterface::from_custom_header("NiFpga_prefix.h")
+    ///    .enum_register("State", "crate::MachineState")
+    ///   .build();
+    /// ```
+    pub fn enum_register(
+        &mut self,
+        register_name: impl Into<String>,
+        enum_type: impl Into<String>,
+    ) -> &mut Self {
+        self.generator_options
+            .enum_types
+            .insert(register_name.into(), enum_type.into());
+        self
+    }
+
+    /// Let the compiler warn about registers and FIFOs which are never used.
+    ///
+    /// By default the generated modules allow dead code as most applications only use a few of the registers.
+    /// Enabling this drops that allow so the compiler reports each unused register or FIFO.
+    /// Use this to find controls and indicators you can remove from the FPGA front panel.
+    ///
+    /// Only the register and FIFO consts are generated in this mode. The helpers which refer to every
+    /// register, like the `{Name}Reg` types, `address_of`, `apply_defaults` and `configure_all`,
+    /// would count as a use of all of them so they are left out.
+    /// The warnings only appear if the generated module is private, e.g. the `mod fpga_defs` shown above.
+    /// ```no_run
+    /// use ni_fpga_interface_build::FpgaCInterface;
+    /// FpgaCInterface::from_custom_header("NiFpga_prefix.h")
+    ///    .warn_unused(true)
+    ///   .build();
+    /// ```
+    pub fn warn_unused(&mut self, enable: bool) -> &mut Self {
+        self.generator_options.warn_unused = enable;
+        self
+    }
+
+    /// Generate bindings which compile against `ni-fpga-interface` with its `std` feature disabled.
+    ///
+    /// The registers and metadata are generated as normal. The `fifos` module and the diagnostics
+    /// `Interface` are left out as the FIFOs and `Session` need `std`.
+    /// ```no_run
+    /// use ni_fpga_interface_build::FpgaCInterface;
+    /// FpgaCInterface::from_custom_header("NiFpga_prefix.h")
+    ///    .no_std(true)
+    ///   .build();
+    /// ```
+    pub fn no_std(&mut self, enable: bool) -> &mut Self {
+        self.generator_options.no_std = enable;
+        self
+    }
+
+    /// Parse the header and report which features the interface uses.
+    ///
+    /// This can be called before [`FpgaCInterface::build`] to check the interface specific `.c` file
+    /// is present when FXP or cluster types need it, rather than finding out from a linker error.
+    ///
+    /// This doesn't panic or print anything so the build script can decide how to report the result.
+    /// An error is returned if the header can't be read or parsed.
+    /// ```no_run
+    /// use ni_fpga_interface_build::FpgaCInterface;
+    /// let mut interface = FpgaCInterface::from_custom_header("NiFpga_prefix.h");
+    /// if interface
+    ///     .analyze()
+    ///     .is_ok_and(|analysis| analysis.is_missing_custom_c())
+    /// {
+    ///     println!("cargo:warning=This interface uses FXP or cluster types; ensure NiFpga_prefix.c is present.");
+    /// }
+    /// interface.build();
+    /// ```
+    pub fn analyze(&self) -> Result<InterfaceAnalysis, HeaderParseError> {
+        let file = bindings_parser::parse_header(&self.header_prefix, &self.custom_h)?;
+        Ok(interface_analysis::analyze_translation_unit(
+            &self.header_prefix,
+            &self.interface_name,
+            &file,
+            self.custom_c.is_some(),
+        ))
+    }
+
     /// Build the C interface and generate rust bindings for it.
     pub fn build(&self) {
+        for path in self.input_files() {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
         self.build_lib();
         self.build_rust_interface();
     }
 
+    /// The files which the build depends on and should trigger a rebuild when they change.
+    ///
+    /// The interface specific `.c` file is only included if it exists, as Cargo reruns the build script
+    /// on every build while a watched file is missing. The FPGA compile generates it with the header
+    /// so a change to the header picks it up.
+    fn input_files(&self) -> Vec<PathBuf> {
+        let mut files = vec![self.custom_h.clone(), self.common_c.clone()];
+        files.extend(self.custom_c.clone());
+        files.extend(self.bitfile.clone());
+        files
+    }
+
     fn build_lib(&self) {
-        let mut build = cc::Build::new();
+        let marker = PathBuf::from(env::var("OUT_DIR").unwrap()).join(COMMON_LIB_MARKER);
 
-        if let Some(path) = &self.sysroot {
-            build.flag(&format!("--sysroot={path}"));
+        for library in self.libraries(common_lib_built(&marker)) {
+            let mut build = self.c_build();
+            if library.common {
+                self.add_common_files(&mut build);
+            }
+            build.files(library.custom_c);
+            build.compile(&library.name);
+            if library.common {
+                mark_common_lib_built(&marker);
+            }
         }
+    }
+
+    /// The libraries to compile for this interface.
+    ///
+    /// Once `NiFpga.c` has been compiled by any interface in the build script only the custom file is compiled
+    /// so it is never linked twice.
+    fn libraries(&self, common_built: bool) -> Vec<CLibrary<'_>> {
+        let specific = self.custom_c.as_deref().map(|custom_c| CLibrary {
+            name: self.specific_lib_name(),
+            common: false,
+            custom_c: Some(custom_c),
+        });
 
+        if common_built {
+            specific.into_iter().collect()
+        } else if self.shared_common {
+            let common = CLibrary {
+                name: "ni_fpga_common".to_owned(),
+                common: true,
+                custom_c: None,
+            };
+            std::iter::once(common).chain(specific).collect()
+        } else {
+            vec![CLibrary {
+                name: "ni_fpga".to_owned(),
+                common: true,
+                custom_c: self.custom_c.as_deref(),
+            }]
+        }
+    }
+
+    /// Add `NiFpga.c` and any shim needed for the API version to the build.
+    fn add_common_files(&self, build: &mut cc::Build) {
         build.file(&self.common_c);
 
-        if let Some(custom_c) = &self.custom_c {
-            build.file(custom_c);
+        if let Ok(source) = std::fs::read_to_string(&self.common_c) {
+            let missing = self.api_version.missing_functions(&source);
+            if !missing.is_empty() {
+                println!(
+                    "cargo:warning={} does not define {}. Set `api_version(ApiVersion::Legacy)` if this is an older C API.",
+                    self.common_c.display(),
+                    missing.join(", ")
+                );
+            }
+        }
+
+        if let Some(shim) = self.api_version.shim_source() {
+            let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
+            let shim_path = out_path.join("NiFpga_api_shim.c");
+            std::fs::write(&shim_path, shim).unwrap();
+            build.file(shim_path);
+            if let Some(interface_folder) = self.common_c.parent() {
+                build.include(interface_folder);
+            }
+        }
+    }
+
+    fn c_build(&self) -> cc::Build {
+        let mut build = cc::Build::new();
+
+        if let Some(path) = &self.sysroot {
+            build.flag(&format!("--sysroot={path}"));
         }
 
-        build.compile("ni_fpga");
+        build
+    }
+
+    /// The name of the library containing the interface specific C code when sharing the common library.
+    fn specific_lib_name(&self) -> String {
+        format!("ni_fpga_{}", self.interface_name)
     }
 
     fn build_rust_interface(&self) {
         // Write the bindings to the $OUT_DIR/bindings.rs file.
         let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
-        let mod_path = out_path.join(format!("NiFpga_{}.rs", self.interface_name));
-        println!("cargo:rerun-if-changed={}", self.custom_h.display());
+        let mod_path = out_path.join(format!("{}{}.rs", self.header_prefix, self.interface_name));
 
         let interface_description = bindings_parser::InterfaceDescription::parse_bindings(
+            &self.header_prefix,
             &self.interface_name,
             &PathBuf::from(&self.custom_h),
         );
 
-        std::fs::write(mod_path, interface_description.generate_rust_output()).unwrap();
+        if let Err(error) = &interface_description.fxp_types {
+            println!("cargo:warning=FXP_INFO was not generated as the FXP type info couldn't be read: {error}");
+        }
+
+        if let Some(python_stub) = &self.python_stub {
+            let stub = python_stub_generator::generate_python_stub(
+                &self.interface_name,
+                &interface_description.signature,
+                &interface_description.registers,
+            );
+            std::fs::write(python_stub, stub).unwrap();
+        }
+
+        let mut output = interface_description.generate_rust_output(&self.generator_options);
+
+        if self.format_with_rustfmt {
+            let rustfmt_command = env::var("RUSTFMT").unwrap_or_else(|_| "rustfmt".to_owned());
+            output = format_generated(&rustfmt_command, self.rustfmt_edition.as_deref(), output);
+        }
+
+        std::fs::write(mod_path, output).unwrap();
     }
 }
 
+/// A static library compiled for an interface.
+#[derive(Debug, PartialEq, Eq)]
+struct CLibrary<'a> {
+    name: String,
+    /// The library includes `NiFpga.c` and any API shim.
+    common: bool,
+    custom_c: Option<&'a Path>,
+}
+
+/// Whether the common library has been compiled by this run of the build script.
+///
+/// The marker holds the ID of the build script process so one left by a previous run is ignored
+/// and `NiFpga.c` is compiled again with any changes.
+fn common_lib_built(marker: &Path) -> bool {
+    std::fs::read_to_string(marker).is_ok_and(|id| id == std::process::id().to_string())
+}
+
+fn mark_common_lib_built(marker: &Path) {
+    std::fs::write(marker, std::process::id().to_string()).unwrap();
+}
+
+/// Find the interface name from the header and the matching `.c` file if it exists.
+///
+/// If the header doesn't start with the prefix then the whole file name is used as the interface name.
+fn find_interface_files(header: &Path, header_prefix: &str) -> (String, Option<PathBuf>) {
+    let file_stem = header.file_stem().unwrap().to_str().unwrap();
+    let interface_name = file_stem
+        .strip_prefix(header_prefix)
+        .unwrap_or(file_stem)
+        .to_owned();
+
+    let custom_c = expected_custom_c(header, header_prefix, &interface_name);
+    let custom_c = if custom_c.exists() {
+        Some(custom_c)
+    } else {
+        None
+    };
+
+    (interface_name, custom_c)
+}
+
+/// The path the interface specific `.c` file should be at, whether or not it exists.
+fn expected_custom_c(header: &Path, header_prefix: &str, interface_name: &str) -> PathBuf {
+    header
+        .parent()
+        .unwrap()
+        .join(format!("{header_prefix}{interface_name}.c"))
+}
+
+/// Find the bitfile next to the header with the same name if it exists.
+fn find_bitfile(header: &Path) -> Option<PathBuf> {
+    let bitfile = header.with_extension("lvbitx");
+    bitfile.exists().then_some(bitfile)
+}
+
+/// Format the generated code with `rustfmt`, keeping the default formatting if it fails.
+fn format_generated(rustfmt_command: &str, edition: Option<&str>, output: String) -> String {
+    match rustfmt(rustfmt_command, edition, &output) {
+        Ok(formatted) => formatted,
+        Err(error) => {
+            println!("cargo:warning=Failed to run rustfmt on the generated interface, using default formatting: {error}");
+            output
+        }
+    }
+}
+
+/// Pipe the source through `rustfmt` and return the formatted output.
+///
+/// rustfmt is run in the current directory so it will find the config for the crate being built.
+/// The edition is only passed if it is set so otherwise the config decides it.
+fn rustfmt(rustfmt_command: &str, edition: Option<&str>, source: &str) -> Result<String, String> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut command = Command::new(rustfmt_command);
+    if let Some(edition) = edition {
+        command.args(["--edition", edition]);
+    }
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    // Write in a block so stdin is closed before we wait on the output.
+    {
+        let mut stdin = child.stdin.take().ok_or("Failed to open rustfmt stdin")?;
+        stdin
+            .write_all(source.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).into_owned());
+    }
+
+    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+}
+
 #[cfg(test)]
 mod test {
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
 
-    use crate::FpgaCInterface;
+    use crate::{
+        common_lib_built, format_generated, mark_common_lib_built, rustfmt, CLibrary,
+        FpgaCInterface,
+    };
 
     #[test]
     fn test_constructs_from_custom_header_relative_path() {
@@ -192,4 +687,159 @@ mod test {
         );
         assert_eq!(fpga_interface.interface_name, "fpga");
     }
+
+    #[test]
+    fn test_rustfmt_disabled_by_default() {
+        let fpga_interface = FpgaCInterface::from_custom_header("./NiFpga_fpga.h");
+        assert!(!fpga_interface.format_with_rustfmt);
+    }
+
+    #[test]
+    fn test_rustfmt_formats_source() {
+        let formatted = rustfmt("rustfmt", None, "pub const A:u8=1;").unwrap();
+        assert_eq!(formatted, "pub const A: u8 = 1;\n");
+    }
+
+    #[test]
+    fn test_rustfmt_uses_edition_when_set() {
+        // async is only a keyword from the 2018 edition.
+        let source = "async fn f() {}\n";
+        assert_eq!(rustfmt("rustfmt", Some("2021"), source).unwrap(), source);
+        assert!(rustfmt("rustfmt", Some("2015"), source).is_err());
+    }
+
+    #[test]
+    fn test_rustfmt_reports_invalid_source() {
+        assert!(rustfmt("rustfmt", None, "pub const A: u8 =").is_err());
+    }
+
+    #[test]
+    fn test_format_generated_falls_back_when_rustfmt_fails() {
+        let output = "pub const A: u8 = 1;\n".to_owned();
+        assert_eq!(
+            format_generated("./missing-rustfmt", None, output.clone()),
+            output
+        );
+    }
+
+    #[test]
+    fn test_input_files_include_c_files_and_bitfile() {
+        let mut fpga_interface = FpgaCInterface::from_custom_header("./NiFpga_fpga.h");
+        // The custom .c file doesn't exist so it isn't watched.
+        assert_eq!(
+            fpga_interface.input_files(),
+            vec![
+                PathBuf::from("./NiFpga_fpga.h"),
+                PathBuf::from("./NiFpga.c")
+            ]
+        );
+
+        fpga_interface.bitfile("./NiFpga_fpga.lvbitx");
+        assert_eq!(
+            fpga_interface.input_files().last(),
+            Some(&PathBuf::from("./NiFpga_fpga.lvbitx"))
+        );
+    }
+
+    #[test]
+    fn test_finds_bitfile_next_to_header() {
+        let folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples/fpga_c_interface");
+        let fpga_interface = FpgaCInterface::from_custom_header(folder.join("NiFpga_Main.h"));
+        assert_eq!(
+            fpga_interface.bitfile,
+            Some(folder.join("NiFpga_Main.lvbitx"))
+        );
+        assert_eq!(fpga_interface.custom_c, Some(folder.join("NiFpga_Main.c")));
+        assert!(fpga_interface
+            .input_files()
+            .contains(&folder.join("NiFpga_Main.c")));
+    }
+
+    #[test]
+    fn test_analyze_missing_header_is_an_error() {
+        let analysis = FpgaCInterface::from_custom_header("./NiFpga_missing.h").analyze();
+        assert!(matches!(analysis, Err(crate::HeaderParseError::Io(_))));
+    }
+
+    fn interface_with_custom_c(name: &str, shared_common: bool) -> FpgaCInterface {
+        let mut fpga_interface = FpgaCInterface::from_custom_header(format!("./NiFpga_{name}.h"));
+        fpga_interface.custom_c = Some(PathBuf::from(format!("./NiFpga_{name}.c")));
+        fpga_interface.shared_common(shared_common);
+        fpga_interface
+    }
+
+    #[test]
+    fn test_libraries_include_common_with_custom_by_default() {
+        let fpga_interface = interface_with_custom_c("Main", false);
+        assert_eq!(
+            fpga_interface.libraries(false),
+            vec![CLibrary {
+                name: "ni_fpga".to_owned(),
+                common: true,
+                custom_c: Some(Path::new("./NiFpga_Main.c")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_libraries_split_common_when_shared() {
+        let fpga_interface = interface_with_custom_c("Main", true);
+        assert_eq!(
+            fpga_interface.libraries(false),
+            vec![
+                CLibrary {
+                    name: "ni_fpga_common".to_owned(),
+                    common: true,
+                    custom_c: None,
+                },
+                CLibrary {
+                    name: "ni_fpga_Main".to_owned(),
+                    common: false,
+                    custom_c: Some(Path::new("./NiFpga_Main.c")),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_libraries_skip_common_once_built_in_either_mode() {
+        for shared_common in [false, true] {
+            let fpga_interface = interface_with_custom_c("Secondary", shared_common);
+            assert_eq!(
+                fpga_interface.libraries(true),
+                vec![CLibrary {
+                    name: "ni_fpga_Secondary".to_owned(),
+                    common: false,
+                    custom_c: Some(Path::new("./NiFpga_Secondary.c")),
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn test_common_lib_marker_only_matches_this_run() {
+        let marker = std::env::temp_dir().join("ni_fpga_common_marker_test.built");
+        std::fs::write(&marker, "stale").unwrap();
+        assert!(!common_lib_built(&marker));
+
+        mark_common_lib_built(&marker);
+        assert!(common_lib_built(&marker));
+        std::fs::remove_file(marker).unwrap();
+    }
+
+    #[test]
+    fn test_specific_lib_name_uses_interface_name() {
+        let mut fpga_interface = FpgaCInterface::from_custom_header("./NiFpga_Main.h");
+        fpga_interface.shared_common(true);
+        assert_eq!(fpga_interface.specific_lib_name(), "ni_fpga_Main");
+    }
+
+    #[test]
+    fn test_constructs_from_header_with_custom_prefix() {
+        let mut fpga_interface = FpgaCInterface::from_custom_header("./Custom_fpga.h");
+        fpga_interface.header_prefix("Custom_");
+        assert_eq!(fpga_interface.interface_name, "fpga");
+        assert_eq!(fpga_interface.header_prefix, "Custom_");
+        assert_eq!(fpga_interface.custom_c, None);
+    }
 }
@@ -0,0 +1,95 @@
+use lang_c::ast::*;
+use lang_c::span::{Node, Span};
+use lang_c::visit::Visit;
+
+use crate::address_definitions::value_from_integer;
+
+/// Finds the value of an integer constant such as `static const uint32_t NiFpga_CApiVersion = 190;`.
+pub struct NumericConstantVisitor {
+    name: String,
+    pub value: Option<u32>,
+}
+
+impl NumericConstantVisitor {
+    /// Create the visitor for the constant with the full name given.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            value: None,
+        }
+    }
+}
+
+/// Extract the constant value from the AST initialiser.
+///
+/// If the initalizer is not an integer literal, then return None.
+fn numeric_constant_from_initializer(initializer: &Initializer) -> Option<u32> {
+    match initializer {
+        Initializer::Expression(expression) => match &expression.node {
+            Expression::Constant(constant) => match &constant.node {
+                Constant::Integer(value) => value_from_integer(value),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl<'ast> Visit<'ast> for NumericConstantVisitor {
+    fn visit_init_declarator(&mut self, declaration: &'ast InitDeclarator, _span: &'ast Span) {
+        // If we've already found the value, then we can stop.
+        if self.value.is_some() {
+            return;
+        }
+
+        if let DeclaratorKind::Identifier(identifier) = &declaration.declarator.node.kind.node {
+            if identifier.node.name == self.name {
+                if let Some(Node {
+                    node: initializer, ..
+                }) = &declaration.initializer
+                {
+                    self.value = numeric_constant_from_initializer(initializer);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NumericConstantVisitor;
+
+    use lang_c::driver::{parse_preprocessed, Config};
+    use lang_c::visit::Visit;
+
+    fn visit_c_code(content: &str, visitor: &mut NumericConstantVisitor) {
+        let config = Config::default();
+        let file = parse_preprocessed(&config, content.to_owned()).unwrap();
+        visitor.visit_translation_unit(&file.unit);
+    }
+
+    #[test]
+    fn test_numeric_constant_extraction() {
+        let content = r#"
+        static const unsigned int NiFpga_CApiVersion = 190;
+        "#;
+
+        let mut visitor = NumericConstantVisitor::new("NiFpga_CApiVersion");
+        visit_c_code(content, &mut visitor);
+
+        assert_eq!(visitor.value, Some(190));
+    }
+
+    #[test]
+    fn test_ignores_non_integer_constant() {
+        let content = r#"
+        static const char* const NiFpga_CApiVersion = "190";
+        "#;
+
+        let mut visitor = NumericConstantVisitor::new("NiFpga_CApiVersion");
+        visit_c_code(content, &mut visitor);
+
+        assert_eq!(visitor.value, None);
+    }
+}
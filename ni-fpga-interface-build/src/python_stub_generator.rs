@@ -0,0 +1,149 @@
+//! Generates a Python module describing the FPGA interface.
+//!
+//! This is generated from the same parsed header as the Rust module
+//! so that Python test scripts using the NI C API through ctypes can't
+//! drift out of sync with the Rust bindings.
+
+use super::address_definitions::AddressKind;
+use super::address_definitions_visitor::AddressSet;
+use std::fmt::Write;
+
+const HEADER: &str = r#"import ctypes
+from collections import namedtuple
+
+Register = namedtuple("Register", ["name", "kind", "datatype", "ctype", "address", "size"])
+Fifo = namedtuple("Fifo", ["name", "kind", "datatype", "ctype", "address"])
+"#;
+
+/// Generates the python module source for the interface.
+///
+/// Registers and FIFOs are placed in `registers` and `fifos` namespaces
+/// to match the layout of the Rust module.
+pub fn generate_python_stub(
+    interface_name: &str,
+    signature: &str,
+    addresses: &AddressSet,
+) -> String {
+    let mut registers = String::new();
+    let mut fifos = String::new();
+
+    for (def, address) in addresses {
+        let ctype = type_string_to_ctype(&def.datatype);
+        match def.kind {
+            AddressKind::Control | AddressKind::Indicator => {
+                writeln!(
+                    registers,
+                    "    {name} = Register(\"{name}\", \"{kind}\", \"{datatype}\", {ctype}, 0x{address:X}, None)",
+                    name = def.name,
+                    kind = def.kind.prefix(),
+                    datatype = def.datatype,
+                )
+                .unwrap();
+            }
+            AddressKind::ControlArray | AddressKind::IndicatorArray => {
+                let mut size_def = def.clone();
+                size_def.kind = def.kind.with_size();
+                let array_size = addresses.get(&size_def).expect("Array size not found.");
+                writeln!(
+                    registers,
+                    "    {name} = Register(\"{name}\", \"{kind}\", \"{datatype}\", {ctype}, 0x{address:X}, {array_size})",
+                    name = def.name,
+                    kind = def.kind.prefix(),
+                    datatype = def.datatype,
+                )
+                .unwrap();
+            }
+            AddressKind::ControlArraySize | AddressKind::IndicatorArraySize => {
+                continue;
+            }
+            AddressKind::HostToTargetFifo | AddressKind::TargetToHostFifo => {
+                writeln!(
+                    fifos,
+                    "    {name} = Fifo(\"{name}\", \"{kind}\", \"{datatype}\", {ctype}, 0x{address:X})",
+                    name = def.name,
+                    kind = def.kind.prefix(),
+                    datatype = def.datatype,
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    // Python needs a statement in an empty class.
+    if registers.is_empty() {
+        registers.push_str("    pass\n");
+    }
+    if fifos.is_empty() {
+        fifos.push_str("    pass\n");
+    }
+
+    format!(
+        "\"\"\"Interface definitions for the {interface_name} FPGA interface.\n\nGenerated by ni-fpga-interface-build. Do not edit.\n\"\"\"\n{HEADER}\nSIGNATURE = \"{signature}\"\n\n\nclass registers:\n{registers}\n\nclass fifos:\n{fifos}"
+    )
+}
+
+fn type_string_to_ctype(type_string: &str) -> &'static str {
+    match type_string {
+        "Bool" => "ctypes.c_uint8",
+        "U8" => "ctypes.c_uint8",
+        "U16" => "ctypes.c_uint16",
+        "U32" => "ctypes.c_uint32",
+        "U64" => "ctypes.c_uint64",
+        "I8" => "ctypes.c_int8",
+        "I16" => "ctypes.c_int16",
+        "I32" => "ctypes.c_int32",
+        "I64" => "ctypes.c_int64",
+        "Sgl" => "ctypes.c_float",
+        "Dbl" => "ctypes.c_double",
+        _ => panic!("Unknown type {}", type_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bindings_parser::InterfaceDescriptionBuilder;
+
+    #[test]
+    fn test_generate_python_stub() {
+        let addresses = InterfaceDescriptionBuilder::new()
+            .control("U8Control", "U8", 0x18002)
+            .array_indicator("SglResultArray", "Sgl", 0x18018, 4)
+            .target_to_host_fifo("NumbersFromFPGA", "U16", 1)
+            .build()
+            .registers;
+
+        let stub = generate_python_stub("Main", "A0613989B20F45FC6E79EB71383493E8", &addresses);
+
+        let expected = r#""""Interface definitions for the Main FPGA interface.
+
+Generated by ni-fpga-interface-build. Do not edit.
+"""
+import ctypes
+from collections import namedtuple
+
+Register = namedtuple("Register", ["name", "kind", "datatype", "ctype", "address", "size"])
+Fifo = namedtuple("Fifo", ["name", "kind", "datatype", "ctype", "address"])
+
+SIGNATURE = "A0613989B20F45FC6E79EB71383493E8"
+
+
+class registers:
+    U8Control = Register("U8Control", "Control", "U8", ctypes.c_uint8, 0x18002, None)
+    SglResultArray = Register("SglResultArray", "IndicatorArray", "Sgl", ctypes.c_float, 0x18018, 4)
+
+
+class fifos:
+    NumbersFromFPGA = Fifo("NumbersFromFPGA", "TargetToHostFifo", "U16", ctypes.c_uint16, 0x1)
+"#;
+
+        assert_eq!(stub, expected);
+    }
+
+    #[test]
+    fn test_empty_namespaces_are_valid_python() {
+        let stub = generate_python_stub("Main", "sig", &AddressSet::new());
+        assert!(stub.contains("class registers:\n    pass\n"));
+        assert!(stub.contains("class fifos:\n    pass\n"));
+    }
+}
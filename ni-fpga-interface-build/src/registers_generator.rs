@@ -1,21 +1,98 @@
-use super::address_definitions::AddressKind;
+use super::address_definitions::{Address, AddressKind};
 use super::address_definitions_visitor::{AddressSet, LocationDefinition};
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, TokenStream};
 use quote::{format_ident, quote, ToTokens, TokenStreamExt};
+use std::collections::{BTreeMap, BTreeSet};
 use std::str::FromStr;
 
+/// Options set on the builder which change the generated code.
+#[derive(Debug, Default, Clone)]
+pub struct GeneratorOptions {
+    /// Maps a register name to the path of the Rust enum used for it.
+    pub enum_types: BTreeMap<String, String>,
+    /// Let the compiler warn about registers and FIFOs which are never used.
+    pub warn_unused: bool,
+    /// Leave out the FIFOs and diagnostics interface which need `std` in the runtime crate.
+    pub no_std: bool,
+}
+
+impl GeneratorOptions {
+    /// The `dead_code` allow for the generated modules.
+    ///
+    /// This is dropped when [`GeneratorOptions::warn_unused`] is set so each register is checked.
+    fn module_dead_code_allow(&self) -> TokenStream {
+        if self.warn_unused {
+            quote! {}
+        } else {
+            quote! { #[allow(dead_code)] }
+        }
+    }
+
+    /// Whether to generate the helpers which refer to every register or FIFO.
+    ///
+    /// Helpers like `address_of`, `apply_defaults` and `configure_all` would count as a use of
+    /// everything they refer to, so they are left out when [`GeneratorOptions::warn_unused`] is set.
+    fn generate_helpers(&self) -> bool {
+        !self.warn_unused
+    }
+}
+
 /// Generates a rust module containing the register definitions.
 /// including the required include statements.
 ///
 /// The module is declared public for easy use.
-pub fn generate_register_module(registers: &AddressSet) -> impl ToTokens {
+pub fn generate_register_module(
+    registers: &AddressSet,
+    options: &GeneratorOptions,
+) -> impl ToTokens {
+    for name in options.enum_types.keys() {
+        let is_scalar_register = registers.keys().any(|def| {
+            &def.name == name && matches!(def.kind, AddressKind::Control | AddressKind::Indicator)
+        });
+        if !is_scalar_register {
+            panic!("Enum type set for {name} which is not a control or indicator.");
+        }
+    }
+
     let mut tokens = quote! {};
+    let mut register_names = Vec::new();
+    let mut defaults = Vec::new();
     for (def, address) in registers {
         match def.kind {
             AddressKind::Control | AddressKind::Indicator => {
-                let register = generate_address_definition(def, *address, None);
+                register_names.push((def.name.as_str(), *address));
+                let (register, register_type) = match options.enum_types.get(&def.name) {
+                    Some(enum_type) => {
+                        let enum_register_type = enum_register_type(def, enum_type);
+                        (
+                            generate_enum_definition(def, *address, enum_type).to_token_stream(),
+                            generate_register_type(def, *address, enum_register_type, quote! {}),
+                        )
+                    }
+                    None => {
+                        let ty = type_string_to_type(&def.datatype);
+                        (
+                            generate_address_definition(def, *address, None).to_token_stream(),
+                            generate_register_type(
+                                def,
+                                *address,
+                                quote! { Register<#ty> },
+                                quote! {},
+                            ),
+                        )
+                    }
+                };
+                if def.kind == AddressKind::Control {
+                    defaults.push(generate_default_field(
+                        def,
+                        options.enum_types.get(&def.name),
+                        None,
+                    ));
+                }
+                let register_type = options.generate_helpers().then_some(register_type);
                 tokens.append_all(quote! {
                     #register
+                    #register_type
                 });
             }
             AddressKind::ControlArray | AddressKind::IndicatorArray => {
@@ -23,8 +100,22 @@ pub fn generate_register_module(registers: &AddressSet) -> impl ToTokens {
                 size_def.kind = def.kind.with_size();
                 let array_size = registers.get(&size_def).expect("Array size not found.");
                 let register = generate_address_definition(def, *address, Some(*array_size));
+                let ty = type_string_to_type(&def.datatype);
+                let size = Literal::usize_unsuffixed(*array_size as usize);
+                let register_type = generate_register_type(
+                    def,
+                    *address,
+                    quote! { ArrayRegister<#ty, #size> },
+                    quote! { pub const SIZE: usize = #size; },
+                );
+                register_names.push((def.name.as_str(), *address));
+                if def.kind == AddressKind::ControlArray {
+                    defaults.push(generate_default_field(def, None, Some(*array_size)));
+                }
+                let register_type = options.generate_helpers().then_some(register_type);
                 tokens.append_all(quote! {
                     #register
+                    #register_type
                 });
             }
             AddressKind::ControlArraySize | AddressKind::IndicatorArraySize => {
@@ -36,21 +127,219 @@ pub fn generate_register_module(registers: &AddressSet) -> impl ToTokens {
         }
     }
 
+    let dead_code_allow = options.module_dead_code_allow();
+
+    if !options.generate_helpers() {
+        return quote! {
+                #[allow(non_upper_case_globals)]
+                pub mod registers {
+                #[allow(unused_imports)]
+                use ni_fpga_interface::registers::{ ArrayRegister, Register };
+
+                #tokens
+            }
+        };
+    }
+
+    check_register_helper_names(&register_names);
+    let address_lookup = generate_address_lookup(&register_names);
+    let defaults = generate_defaults(&defaults);
+
     // Seeing as we can't control the input naming conventions we allow non-upper-case.
     // probably we could assume Camel Case and convert but I bet that isn't very consistent.
     quote! {
             #[allow(non_upper_case_globals)]
-            #[allow(dead_code)]
+            #dead_code_allow
             pub mod registers {
-            use ni_fpga_interface::registers::{ ArrayRegister, Register};
+            use ni_fpga_interface::registers::{ ArrayRegister, Register, RegisterKind };
+            use ni_fpga_interface::session::RegisterAddress;
 
             #tokens
+
+            #address_lookup
+
+            #defaults
+        }
+    }
+}
+
+/// Panics if a register name clashes with a name generated for the helpers in the registers module.
+///
+/// The `{Name}Reg` unit structs are in both the type and value namespaces so they can clash with the
+/// register consts. The helper functions can clash with the consts and their parameters become
+/// const patterns if a register shares their name.
+fn check_register_helper_names(register_names: &[(&str, Address)]) {
+    let mut values = vec![
+        (
+            "address_of".to_owned(),
+            "the `address_of` helper".to_owned(),
+        ),
+        (
+            "apply_defaults".to_owned(),
+            "the `apply_defaults` helper".to_owned(),
+        ),
+    ];
+    for parameter in ["name", "session", "defaults", "value"] {
+        values.push((
+            parameter.to_owned(),
+            format!("the `{parameter}` variable in the generated helpers"),
+        ));
+    }
+    let mut types = vec![("Defaults".to_owned(), "the `Defaults` type".to_owned())];
+    for (name, _) in register_names {
+        values.push((name.to_string(), format!("register {name}")));
+        values.push((
+            format!("{name}Reg"),
+            format!("the type for register {name}"),
+        ));
+        types.push((
+            format!("{name}Reg"),
+            format!("the type for register {name}"),
+        ));
+    }
+    check_unique_names("registers", &values);
+    check_unique_names("registers", &types);
+}
+
+/// Panics if two of the names generated in the same namespace of a module are the same.
+///
+/// Each name is paired with what generated it so the message names the register or FIFO.
+/// The clash can only be fixed by renaming it in the FPGA VI, or the helpers can be left out with `warn_unused`.
+fn check_unique_names(module: &str, names: &[(String, String)]) {
+    let mut seen: BTreeMap<&str, &str> = BTreeMap::new();
+    for (name, source) in names {
+        if let Some(other) = seen.insert(name, source) {
+            panic!(
+                "The generated `{module}::{name}` for {source} clashes with {other}. \
+                 Rename it in the FPGA VI or leave out the generated helpers with `warn_unused`."
+            );
+        }
+    }
+}
+
+/// Generate a const fn to find a register address from its name.
+///
+/// This matches on the bytes as string comparison isn't available in const fns.
+fn generate_address_lookup(names: &[(&str, Address)]) -> TokenStream {
+    let arms = names.iter().map(|(name, address)| {
+        let pattern = Literal::byte_string(name.as_bytes());
+        let address = TokenStream::from_str(&format!("0x{:X}", address)).unwrap();
+        quote! { #pattern => Some(#address), }
+    });
+
+    quote! {
+        /// Looks up the address of a register by name.
+        ///
+        /// As this is a `const fn` it can be used to build tables at compile time.
+        pub const fn address_of(name: &str) -> Option<RegisterAddress> {
+            match name.as_bytes() {
+                #(#arms)*
+                _ => None,
+            }
+        }
+    }
+}
+
+/// The field and write statement for a control in the generated `Defaults`.
+struct DefaultField {
+    field: TokenStream,
+    write: TokenStream,
+    /// The LabVIEW name of the type written to the FPGA e.g. `U8`.
+    datatype: String,
+}
+
+fn generate_default_field(
+    definition: &LocationDefinition,
+    enum_type: Option<&String>,
+    array_size: Option<u32>,
+) -> DefaultField {
+    let name = format_ident!("{}", definition.name);
+    let ty = type_string_to_type(&definition.datatype);
+    let datatype = definition.datatype.clone();
+    match (enum_type, array_size) {
+        (Some(enum_type), _) => {
+            let enum_ty: syn::Type = syn::parse_str(enum_type).expect("Invalid enum type path");
+            DefaultField {
+                field: quote! { pub #name: Option<#enum_ty> },
+                write: quote! {
+                    if let Some(value) = &defaults.#name {
+                        #name.write(session, value.clone())?;
+                    }
+                },
+                datatype,
+            }
+        }
+        (None, Some(size)) => {
+            let size = Literal::usize_unsuffixed(size as usize);
+            DefaultField {
+                field: quote! { pub #name: Option<[#ty; #size]> },
+                write: quote! {
+                    if let Some(value) = &defaults.#name {
+                        #name.write(session, value)?;
+                    }
+                },
+                datatype,
+            }
+        }
+        (None, None) => DefaultField {
+            field: quote! { pub #name: Option<#ty> },
+            write: quote! {
+                if let Some(value) = defaults.#name {
+                    #name.write(session, value)?;
+                }
+            },
+            datatype,
+        },
+    }
+}
+
+/// Generate a struct with an optional value for each control and a function to write them.
+fn generate_defaults(defaults: &[DefaultField]) -> TokenStream {
+    let fields = defaults.iter().map(|default| &default.field);
+    let writes = defaults.iter().map(|default| &default.write);
+    let datatypes: BTreeSet<&str> = defaults
+        .iter()
+        .map(|default| default.datatype.as_str())
+        .collect();
+    let bounds = datatypes.into_iter().map(|datatype| {
+        let ty = type_string_to_type(datatype);
+        quote! { ni_fpga_interface::session::RegisterInterface<#ty> }
+    });
+    let where_clause = if defaults.is_empty() {
+        quote! {}
+    } else {
+        quote! { where S: #(#bounds)+* }
+    };
+
+    quote! {
+        /// Values to write to the controls with [`apply_defaults`].
+        ///
+        /// Controls left as `None` are not written.
+        #[allow(non_snake_case)]
+        #[derive(Default)]
+        pub struct Defaults {
+            #(#fields,)*
+        }
+
+        /// Write the values set in the defaults to the controls.
+        ///
+        /// This works with any backend which can write the control types, e.g. a `Session`.
+        /// This stops at the first error.
+        #[allow(unused_variables)]
+        pub fn apply_defaults<S>(
+            session: &S,
+            defaults: &Defaults,
+        ) -> Result<(), ni_fpga_interface::error::FPGAError>
+        #where_clause
+        {
+            #(#writes)*
+            Ok(())
         }
     }
 }
 
 /// Generate a seperate module with the FIFO definitions.
-pub fn generate_fifo_module(addresses: &AddressSet) -> impl ToTokens {
+pub fn generate_fifo_module(addresses: &AddressSet, options: &GeneratorOptions) -> impl ToTokens {
     let mut tokens = quote! {};
     for (def, address) in addresses {
         match def.kind {
@@ -65,19 +354,194 @@ pub fn generate_fifo_module(addresses: &AddressSet) -> impl ToTokens {
             }
         }
     }
+    if options.generate_helpers() {
+        check_fifo_helper_names(addresses);
+        tokens.append_all(generate_fifo_channels(addresses));
+        tokens.append_all(generate_fifo_list(addresses));
+    }
+    let dead_code_allow = options.module_dead_code_allow();
+    let unused_imports_allow = if options.generate_helpers() {
+        quote! {}
+    } else {
+        quote! { #[allow(unused_imports)] }
+    };
 
     // Seeing as we can't control the input naming conventions we allow non-upper-case.
     // probably we could assume Camel Case and convert but I bet that isn't very consistent.
     quote! {
             #[allow(non_upper_case_globals)]
-            #[allow(dead_code)]
+            #dead_code_allow
             pub mod fifos {
+            #unused_imports_allow
             use ni_fpga_interface::fifos::{ ReadFifo, WriteFifo };
             #tokens
         }
     }
 }
 
+/// Panics if a FIFO name clashes with a name generated for the helpers in the FIFOs module.
+fn check_fifo_helper_names(addresses: &AddressSet) {
+    let mut values = vec![
+        ("ALL_FIFOS".to_owned(), "the `ALL_FIFOS` list".to_owned()),
+        (
+            "configure_all".to_owned(),
+            "the `configure_all` helper".to_owned(),
+        ),
+    ];
+    for parameter in ["session", "plan"] {
+        values.push((
+            parameter.to_owned(),
+            format!("the `{parameter}` variable in the generated helpers"),
+        ));
+    }
+    for def in addresses.keys().filter(|def| {
+        matches!(
+            def.kind,
+            AddressKind::HostToTargetFifo | AddressKind::TargetToHostFifo
+        )
+    }) {
+        values.push((def.name.clone(), format!("FIFO {}", def.name)));
+    }
+    for (base, write_def, _) in fifo_channel_pairs(addresses) {
+        values.push((
+            format!("{base}Channel"),
+            format!("the channel for FIFO {}", write_def.name),
+        ));
+    }
+    check_unique_names("fifos", &values);
+}
+
+/// Generate the list of every FIFO and a `configure_all` to set them all up from a plan.
+fn generate_fifo_list(addresses: &AddressSet) -> TokenStream {
+    let fifos = addresses
+        .iter()
+        .filter(|(def, _)| {
+            matches!(
+                def.kind,
+                AddressKind::HostToTargetFifo | AddressKind::TargetToHostFifo
+            )
+        })
+        .map(|(def, address)| {
+            let name = &def.name;
+            let address = TokenStream::from_str(&format!("0x{:X}", address)).unwrap();
+            quote! { (#name, #address) }
+        });
+
+    quote! {
+        pub const ALL_FIFOS: &[(&str, ni_fpga_interface::session::FifoAddress)] = &[#(#fifos),*];
+        pub fn configure_all(
+            session: &impl ni_fpga_interface::session::FifoControlInterface,
+            plan: &ni_fpga_interface::fifos::FifoPlan,
+        ) -> Result<ni_fpga_interface::fifos::FifoDepths, ni_fpga_interface::fifos::FifoPlanError> {
+            ni_fpga_interface::fifos::configure_all(session, ALL_FIFOS, plan)
+        }
+    }
+}
+
+/// Find the pairs of FIFOs named `{Name}ToFPGA` and `{Name}FromFPGA`, returning the name and the write and read FIFOs.
+fn fifo_channel_pairs(
+    addresses: &AddressSet,
+) -> Vec<(&str, &LocationDefinition, &LocationDefinition)> {
+    let mut pairs = Vec::new();
+    for def in addresses.keys() {
+        if def.kind != AddressKind::HostToTargetFifo {
+            continue;
+        }
+        let Some(base) = def
+            .name
+            .strip_suffix("ToFPGA")
+            .filter(|base| !base.is_empty())
+        else {
+            continue;
+        };
+        let Some(read_def) = addresses.keys().find(|read_def| {
+            read_def.kind == AddressKind::TargetToHostFifo
+                && read_def.name == format!("{base}FromFPGA")
+        }) else {
+            continue;
+        };
+        pairs.push((base, def, read_def));
+    }
+    pairs
+}
+
+/// Generate a channel for each pair of FIFOs named `{Name}ToFPGA` and `{Name}FromFPGA`.
+fn generate_fifo_channels(addresses: &AddressSet) -> TokenStream {
+    let mut tokens = quote! {};
+    for (base, def, read_def) in fifo_channel_pairs(addresses) {
+        let name = format_ident!("{}Channel", base);
+        let write_name = format_ident!("{}", def.name);
+        let read_name = format_ident!("{}", read_def.name);
+        let write_type = type_string_to_type(&def.datatype);
+        let read_type = type_string_to_type(&read_def.datatype);
+        tokens.append_all(quote! {
+            pub const #name: ni_fpga_interface::fifos::FifoChannel<#write_type, #read_type> =
+                ni_fpga_interface::fifos::FifoChannel::new(#write_name, #read_name);
+        });
+    }
+    tokens
+}
+
+/// Generate the `Interface` type which describes the interface for session diagnostics.
+///
+/// This reads the indicators by address rather than through the register types
+/// so it doesn't count as a use of them.
+pub fn generate_interface_impl(addresses: &AddressSet) -> TokenStream {
+    let mut indicators = Vec::new();
+    let mut fifos = Vec::new();
+    for (def, address) in addresses {
+        let name = &def.name;
+        let ty = type_string_to_type(&def.datatype);
+        let address = TokenStream::from_str(&format!("0x{:X}", address)).unwrap();
+        match def.kind {
+            AddressKind::Indicator => indicators.push(quote! {
+                (#name, format!("{:?}", ni_fpga_interface::registers::Register::<#ty>::new(#address).read(session)?))
+            }),
+            AddressKind::IndicatorArray => {
+                let mut size_def = def.clone();
+                size_def.kind = def.kind.with_size();
+                let size = array_size_literal(addresses, &size_def);
+                indicators.push(quote! {
+                    (#name, format!("{:?}", ni_fpga_interface::registers::ArrayRegister::<#ty, #size>::new(#address).read(session)?))
+                });
+            }
+            AddressKind::HostToTargetFifo | AddressKind::TargetToHostFifo => {
+                fifos.push(quote! { (#name, #address) })
+            }
+            _ => {}
+        }
+    }
+
+    quote! {
+        /// Describes this interface for [`ni_fpga_interface::session::Session::diagnostics`].
+        #[allow(dead_code)]
+        pub struct Interface;
+
+        impl ni_fpga_interface::session::diagnostics::FpgaInterface for Interface {
+            fn signature(&self) -> &'static str {
+                SIGNATURE
+            }
+
+            fn fifos(&self) -> &'static [(&'static str, ni_fpga_interface::session::FifoAddress)] {
+                &[#(#fifos),*]
+            }
+
+            fn read_indicators(
+                &self,
+                session: &ni_fpga_interface::session::Session,
+            ) -> Result<Vec<(&'static str, String)>, ni_fpga_interface::error::FPGAError> {
+                Ok(vec![#(#indicators),*])
+            }
+        }
+    }
+}
+
+/// The size of an array register as a literal.
+fn array_size_literal(addresses: &AddressSet, size_def: &LocationDefinition) -> Literal {
+    let size = addresses.get(size_def).expect("Array size not found.");
+    Literal::usize_unsuffixed(*size as usize)
+}
+
 fn type_string_to_type(type_string: &str) -> impl ToTokens {
     match type_string {
         "U8" => quote! {u8},
@@ -94,7 +558,72 @@ fn type_string_to_type(type_string: &str) -> impl ToTokens {
     }
 }
 
-/// Generates the definition for a single register.
+/// The `EnumRegister` type for a register which holds a LabVIEW enum.
+fn enum_register_type(definition: &LocationDefinition, enum_type: &str) -> TokenStream {
+    let raw_ty = type_string_to_type(&definition.datatype);
+    let enum_ty: syn::Type = syn::parse_str(enum_type).expect("Invalid enum type path");
+    quote! { ni_fpga_interface::registers::EnumRegister<#enum_ty, #raw_ty> }
+}
+
+/// Generates the definition for a register which holds a LabVIEW enum.
+///
+/// The C API only gives us the underlying integer type so the enum type
+/// is provided by the user as a path to their own enum.
+fn generate_enum_definition(
+    definition: &LocationDefinition,
+    address: Address,
+    enum_type: &str,
+) -> impl ToTokens {
+    let name = format_ident!("{}", definition.name);
+    let register_type = enum_register_type(definition, enum_type);
+    let address = TokenStream::from_str(&format!("0x{:X}", address)).unwrap();
+
+    quote! {
+        pub const #name: #register_type = ni_fpga_interface::registers::EnumRegister::new(#address);
+    }
+}
+
+/// Generates the type for a single register to go alongside its const.
+///
+/// This is a zero sized type named `{Name}Reg` with the address and kind as associated constants.
+/// It dereferences to the register const so the typed read and write methods can be called
+/// directly on it. e.g. `registers::U8ControlReg.read(&session)`.
+///
+/// `extra_constants` are added to the associated constants e.g. for the size of arrays.
+fn generate_register_type(
+    definition: &LocationDefinition,
+    address: Address,
+    register_type: TokenStream,
+    extra_constants: TokenStream,
+) -> TokenStream {
+    let name = format_ident!("{}", definition.name);
+    let type_name = format_ident!("{}Reg", definition.name);
+    let address = TokenStream::from_str(&format!("0x{:X}", address)).unwrap();
+    let kind = match definition.kind {
+        AddressKind::Control | AddressKind::ControlArray => quote! { RegisterKind::Control },
+        _ => quote! { RegisterKind::Indicator },
+    };
+
+    quote! {
+        #[derive(Clone, Copy)]
+        pub struct #type_name;
+        impl #type_name {
+            pub const ADDRESS: RegisterAddress = #address;
+            pub const KIND: RegisterKind = #kind;
+            #extra_constants
+        }
+
+        impl core::ops::Deref for #type_name {
+            type Target = #register_type;
+
+            fn deref(&self) -> &Self::Target {
+                &#name
+            }
+        }
+    }
+}
+
+/// Generates the definition for a single register or FIFO.
 /// This is a const definition of a Register<T> or ArrayRegister<T, N> depending on the kind of register.
 ///
 /// # Arguments
@@ -104,7 +633,7 @@ fn type_string_to_type(type_string: &str) -> impl ToTokens {
 /// at a higher level.
 fn generate_address_definition(
     definition: &LocationDefinition,
-    address: u32,
+    address: Address,
     array_size: Option<u32>,
 ) -> impl ToTokens {
     let name = format_ident!("{}", definition.name);
@@ -143,6 +672,7 @@ fn generate_address_definition(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bindings_parser::InterfaceDescriptionBuilder;
 
     #[test]
     fn test_generate_control_register() {
@@ -301,15 +831,67 @@ mod tests {
             0x1800A,
         );
 
-        let tokens = generate_register_module(&registers);
+        let tokens = generate_register_module(&registers, &GeneratorOptions::default());
 
         let expected = quote! {
             #[allow(non_upper_case_globals)]
             #[allow(dead_code)]
             pub mod registers {
-                use ni_fpga_interface::registers::{ ArrayRegister, Register};
+                use ni_fpga_interface::registers::{ ArrayRegister, Register, RegisterKind };
+                use ni_fpga_interface::session::RegisterAddress;
 
                 pub const control: Register<u8> = Register::new(0x1800A);
+                #[derive(Clone, Copy)]
+                pub struct controlReg;
+
+                impl controlReg {
+                    pub const ADDRESS: RegisterAddress = 0x1800A;
+                    pub const KIND: RegisterKind = RegisterKind::Control;
+                }
+
+                impl core::ops::Deref for controlReg {
+                    type Target = Register<u8>;
+
+                    fn deref(&self) -> &Self::Target {
+                        &control
+                    }
+                }
+                /// Looks up the address of a register by name.
+                ///
+                /// As this is a `const fn` it can be used to build tables at compile time.
+                pub const fn address_of(name: &str) -> Option<RegisterAddress> {
+                    match name.as_bytes() {
+                        b"control" => Some(0x1800A),
+                        _ => None,
+                    }
+                }
+
+                /// Values to write to the controls with [`apply_defaults`].
+                ///
+                /// Controls left as `None` are not written.
+                #[allow(non_snake_case)]
+                #[derive(Default)]
+                pub struct Defaults {
+                    pub control: Option<u8>,
+                }
+
+                /// Write the values set in the defaults to the controls.
+                ///
+                /// This works with any backend which can write the control types, e.g. a `Session`.
+                /// This stops at the first error.
+                #[allow(unused_variables)]
+                pub fn apply_defaults<S>(
+                    session: &S,
+                    defaults: &Defaults,
+                ) -> Result<(), ni_fpga_interface::error::FPGAError>
+                where
+                    S: ni_fpga_interface::session::RegisterInterface<u8>
+                {
+                    if let Some(value) = defaults.control {
+                        control.write(session, value)?;
+                    }
+                    Ok(())
+                }
             }
         };
 
@@ -336,15 +918,67 @@ mod tests {
             0x01,
         );
 
-        let tokens = generate_register_module(&registers);
+        let tokens = generate_register_module(&registers, &GeneratorOptions::default());
 
         let expected = quote! {
             #[allow(non_upper_case_globals)]
             #[allow(dead_code)]
             pub mod registers {
-                use ni_fpga_interface::registers::{ ArrayRegister, Register};
+                use ni_fpga_interface::registers::{ ArrayRegister, Register, RegisterKind };
+                use ni_fpga_interface::session::RegisterAddress;
 
                 pub const control: Register<u8> = Register::new(0x1800A);
+                #[derive(Clone, Copy)]
+                pub struct controlReg;
+
+                impl controlReg {
+                    pub const ADDRESS: RegisterAddress = 0x1800A;
+                    pub const KIND: RegisterKind = RegisterKind::Control;
+                }
+
+                impl core::ops::Deref for controlReg {
+                    type Target = Register<u8>;
+
+                    fn deref(&self) -> &Self::Target {
+                        &control
+                    }
+                }
+                /// Looks up the address of a register by name.
+                ///
+                /// As this is a `const fn` it can be used to build tables at compile time.
+                pub const fn address_of(name: &str) -> Option<RegisterAddress> {
+                    match name.as_bytes() {
+                        b"control" => Some(0x1800A),
+                        _ => None,
+                    }
+                }
+
+                /// Values to write to the controls with [`apply_defaults`].
+                ///
+                /// Controls left as `None` are not written.
+                #[allow(non_snake_case)]
+                #[derive(Default)]
+                pub struct Defaults {
+                    pub control: Option<u8>,
+                }
+
+                /// Write the values set in the defaults to the controls.
+                ///
+                /// This works with any backend which can write the control types, e.g. a `Session`.
+                /// This stops at the first error.
+                #[allow(unused_variables)]
+                pub fn apply_defaults<S>(
+                    session: &S,
+                    defaults: &Defaults,
+                ) -> Result<(), ni_fpga_interface::error::FPGAError>
+                where
+                    S: ni_fpga_interface::session::RegisterInterface<u8>
+                {
+                    if let Some(value) = defaults.control {
+                        control.write(session, value)?;
+                    }
+                    Ok(())
+                }
             }
         };
 
@@ -353,39 +987,128 @@ mod tests {
 
     #[test]
     fn test_should_extract_size_from_set_for_array_register() {
-        let mut registers = AddressSet::new();
-        registers.insert(
-            LocationDefinition {
-                name: "control".to_string(),
-                datatype: "U8".to_string(),
-                kind: AddressKind::ControlArray,
-            },
-            0x1800A,
-        );
-        registers.insert(
-            LocationDefinition {
-                name: "control".to_string(),
-                datatype: "U8".to_string(),
-                kind: AddressKind::ControlArraySize,
-            },
-            5,
-        );
+        let registers = InterfaceDescriptionBuilder::new()
+            .array_control("control", "U8", 0x1800A, 5)
+            .build()
+            .registers;
 
-        let tokens = generate_register_module(&registers);
+        let tokens = generate_register_module(&registers, &GeneratorOptions::default());
 
         let expected = quote! {
             #[allow(non_upper_case_globals)]
             #[allow(dead_code)]
             pub mod registers {
-                use ni_fpga_interface::registers::{ ArrayRegister, Register};
+                use ni_fpga_interface::registers::{ ArrayRegister, Register, RegisterKind };
+                use ni_fpga_interface::session::RegisterAddress;
 
                 pub const control: ArrayRegister<u8, 5> = ArrayRegister::new(0x1800A);
+                #[derive(Clone, Copy)]
+                pub struct controlReg;
+
+                impl controlReg {
+                    pub const ADDRESS: RegisterAddress = 0x1800A;
+                    pub const KIND: RegisterKind = RegisterKind::Control;
+                    pub const SIZE: usize = 5;
+                }
+
+                impl core::ops::Deref for controlReg {
+                    type Target = ArrayRegister<u8, 5>;
+
+                    fn deref(&self) -> &Self::Target {
+                        &control
+                    }
+                }
+                /// Looks up the address of a register by name.
+                ///
+                /// As this is a `const fn` it can be used to build tables at compile time.
+                pub const fn address_of(name: &str) -> Option<RegisterAddress> {
+                    match name.as_bytes() {
+                        b"control" => Some(0x1800A),
+                        _ => None,
+                    }
+                }
+
+                /// Values to write to the controls with [`apply_defaults`].
+                ///
+                /// Controls left as `None` are not written.
+                #[allow(non_snake_case)]
+                #[derive(Default)]
+                pub struct Defaults {
+                    pub control: Option<[u8; 5]>,
+                }
+
+                /// Write the values set in the defaults to the controls.
+                ///
+                /// This works with any backend which can write the control types, e.g. a `Session`.
+                /// This stops at the first error.
+                #[allow(unused_variables)]
+                pub fn apply_defaults<S>(
+                    session: &S,
+                    defaults: &Defaults,
+                ) -> Result<(), ni_fpga_interface::error::FPGAError>
+                where
+                    S: ni_fpga_interface::session::RegisterInterface<u8>
+                {
+                    if let Some(value) = &defaults.control {
+                        control.write(session, value)?;
+                    }
+                    Ok(())
+                }
             }
         };
 
         assert_eq!(tokens.to_token_stream().to_string(), expected.to_string());
     }
 
+    #[test]
+    fn test_apply_defaults_bounds_each_control_type_once() {
+        let registers = InterfaceDescriptionBuilder::new()
+            .control("First", "U8", 0x18002)
+            .control("Second", "U8", 0x18006)
+            .array_control("Gains", "Sgl", 0x18010, 4)
+            .indicator("Result", "I64", 0x18020)
+            .build()
+            .registers;
+
+        let tokens = generate_register_module(&registers, &GeneratorOptions::default())
+            .to_token_stream()
+            .to_string();
+
+        let bounds = quote! {
+            where
+                S: ni_fpga_interface::session::RegisterInterface<f32>
+                    + ni_fpga_interface::session::RegisterInterface<u8>
+        };
+        assert!(tokens.contains(&bounds.to_string()));
+    }
+
+    #[test]
+    fn test_interface_reads_indicators_and_lists_fifos() {
+        let description = InterfaceDescriptionBuilder::new()
+            .control("U8Control", "U8", 0x18002)
+            .indicator("U8Result", "U8", 0x18006)
+            .array_indicator("Results", "I32", 0x18010, 4)
+            .target_to_host_fifo("NumbersFromFPGA", "U16", 0x1)
+            .build();
+
+        let tokens = generate_interface_impl(&description.registers).to_string();
+
+        assert!(tokens.contains(
+            &quote! {
+                ("U8Result", format!("{:?}", ni_fpga_interface::registers::Register::<u8>::new(0x18006).read(session)?))
+            }
+            .to_string()
+        ));
+        assert!(tokens.contains(
+            &quote! {
+                ("Results", format!("{:?}", ni_fpga_interface::registers::ArrayRegister::<i32, 4>::new(0x18010).read(session)?))
+            }
+            .to_string()
+        ));
+        assert!(!tokens.contains("\"U8Control\""));
+        assert!(tokens.contains(&quote! { &[("NumbersFromFPGA", 0x1)] }.to_string()));
+    }
+
     #[test]
     fn test_should_generate_a_public_module_with_fifos() {
         let mut registers = AddressSet::new();
@@ -414,7 +1137,7 @@ mod tests {
             0x1800A,
         );
 
-        let tokens = generate_fifo_module(&registers);
+        let tokens = generate_fifo_module(&registers, &GeneratorOptions::default());
 
         let expected = quote! {
             #[allow(non_upper_case_globals)]
@@ -424,9 +1147,209 @@ mod tests {
 
                 pub const from_fpga: ReadFifo<f32> = ReadFifo::new(0x2);
                 pub const to_fpga: WriteFifo<u8> = WriteFifo::new(0x1);
+
+                pub const ALL_FIFOS: &[(&str, ni_fpga_interface::session::FifoAddress)] = &[("from_fpga", 0x2), ("to_fpga", 0x1)];
+
+                pub fn configure_all(
+                    session: &impl ni_fpga_interface::session::FifoControlInterface,
+                    plan: &ni_fpga_interface::fifos::FifoPlan,
+                ) -> Result<ni_fpga_interface::fifos::FifoDepths, ni_fpga_interface::fifos::FifoPlanError> {
+                    ni_fpga_interface::fifos::configure_all(session, ALL_FIFOS, plan)
+                }
+            }
+        };
+
+        assert_eq!(tokens.to_token_stream().to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_should_generate_channels_for_fifo_pairs() {
+        let registers = InterfaceDescriptionBuilder::new()
+            .host_to_target_fifo("NumbersToFPGA", "U32", 0x00)
+            .target_to_host_fifo("NumbersFromFPGA", "U16", 0x01)
+            .target_to_host_fifo("LogFromFPGA", "U8", 0x02)
+            .build()
+            .registers;
+
+        let tokens = generate_fifo_channels(&registers);
+
+        let expected = quote! {
+            pub const NumbersChannel: ni_fpga_interface::fifos::FifoChannel<u32, u16> =
+                ni_fpga_interface::fifos::FifoChannel::new(NumbersToFPGA, NumbersFromFPGA);
+        };
+
+        assert_eq!(tokens.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_generate_enum_indicator() {
+        let registers = InterfaceDescriptionBuilder::new()
+            .indicator("State", "U16", 0x1800A)
+            .build()
+            .registers;
+        let mut options = GeneratorOptions::default();
+        options
+            .enum_types
+            .insert("State".to_string(), "crate::MachineState".to_string());
+
+        let tokens = generate_register_module(&registers, &options);
+
+        let expected = quote! {
+            #[allow(non_upper_case_globals)]
+            #[allow(dead_code)]
+            pub mod registers {
+                use ni_fpga_interface::registers::{ ArrayRegister, Register, RegisterKind };
+                use ni_fpga_interface::session::RegisterAddress;
+
+                pub const State: ni_fpga_interface::registers::EnumRegister<crate::MachineState, u16> = ni_fpga_interface::registers::EnumRegister::new(0x1800A);
+                #[derive(Clone, Copy)]
+                pub struct StateReg;
+
+                impl StateReg {
+                    pub const ADDRESS: RegisterAddress = 0x1800A;
+                    pub const KIND: RegisterKind = RegisterKind::Indicator;
+                }
+
+                impl core::ops::Deref for StateReg {
+                    type Target = ni_fpga_interface::registers::EnumRegister<crate::MachineState, u16>;
+
+                    fn deref(&self) -> &Self::Target {
+                        &State
+                    }
+                }
+                /// Looks up the address of a register by name.
+                ///
+                /// As this is a `const fn` it can be used to build tables at compile time.
+                pub const fn address_of(name: &str) -> Option<RegisterAddress> {
+                    match name.as_bytes() {
+                        b"State" => Some(0x1800A),
+                        _ => None,
+                    }
+                }
+
+                /// Values to write to the controls with [`apply_defaults`].
+                ///
+                /// Controls left as `None` are not written.
+                #[allow(non_snake_case)]
+                #[derive(Default)]
+                pub struct Defaults {
+                }
+
+                /// Write the values set in the defaults to the controls.
+                ///
+                /// This works with any backend which can write the control types, e.g. a `Session`.
+                /// This stops at the first error.
+                #[allow(unused_variables)]
+                pub fn apply_defaults<S>(
+                    session: &S,
+                    defaults: &Defaults,
+                ) -> Result<(), ni_fpga_interface::error::FPGAError> {
+                    Ok(())
+                }
             }
         };
 
         assert_eq!(tokens.to_token_stream().to_string(), expected.to_string());
     }
+
+    #[test]
+    fn test_warn_unused_only_generates_consts() {
+        let registers = InterfaceDescriptionBuilder::new()
+            .control("Count", "U32", 0x18000)
+            .host_to_target_fifo("NumbersToFPGA", "U32", 0x0)
+            .target_to_host_fifo("NumbersFromFPGA", "U32", 0x1)
+            .build()
+            .registers;
+        let options = GeneratorOptions {
+            warn_unused: true,
+            ..Default::default()
+        };
+
+        let tokens = generate_register_module(&registers, &options);
+        let expected = quote! {
+            #[allow(non_upper_case_globals)]
+            pub mod registers {
+                #[allow(unused_imports)]
+                use ni_fpga_interface::registers::{ ArrayRegister, Register };
+
+                pub const Count: Register<u32> = Register::new(0x18000);
+            }
+        };
+        assert_eq!(tokens.to_token_stream().to_string(), expected.to_string());
+
+        let tokens = generate_fifo_module(&registers, &options);
+        let expected = quote! {
+            #[allow(non_upper_case_globals)]
+            pub mod fifos {
+                #[allow(unused_imports)]
+                use ni_fpga_interface::fifos::{ ReadFifo, WriteFifo };
+                pub const NumbersFromFPGA: ReadFifo<u32> = ReadFifo::new(0x1);
+                pub const NumbersToFPGA: WriteFifo<u32> = WriteFifo::new(0x0);
+            }
+        };
+        assert_eq!(tokens.to_token_stream().to_string(), expected.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "not a control or indicator")]
+    fn test_enum_type_for_unknown_register_panics() {
+        let registers = AddressSet::new();
+        let mut options = GeneratorOptions::default();
+        options
+            .enum_types
+            .insert("Missing".to_string(), "crate::MachineState".to_string());
+
+        generate_register_module(&registers, &options);
+    }
+
+    #[test]
+    #[should_panic(expected = "`registers::GainReg`")]
+    fn test_register_clashing_with_register_type_panics() {
+        let registers = InterfaceDescriptionBuilder::new()
+            .control("Gain", "U8", 0x18002)
+            .control("GainReg", "U8", 0x18006)
+            .build()
+            .registers;
+
+        generate_register_module(&registers, &GeneratorOptions::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "`registers::address_of` for register address_of")]
+    fn test_register_clashing_with_helper_panics() {
+        let registers = InterfaceDescriptionBuilder::new()
+            .indicator("address_of", "U8", 0x18002)
+            .build()
+            .registers;
+
+        generate_register_module(&registers, &GeneratorOptions::default());
+    }
+
+    #[test]
+    fn test_register_names_unchecked_without_helpers() {
+        let registers = InterfaceDescriptionBuilder::new()
+            .control("Gain", "U8", 0x18002)
+            .control("GainReg", "U8", 0x18006)
+            .build()
+            .registers;
+        let options = GeneratorOptions {
+            warn_unused: true,
+            ..Default::default()
+        };
+
+        generate_register_module(&registers, &options);
+    }
+
+    #[test]
+    #[should_panic(expected = "`fifos::NumbersChannel`")]
+    fn test_fifo_clashing_with_channel_panics() {
+        let registers = InterfaceDescriptionBuilder::new()
+            .host_to_target_fifo("NumbersToFPGA", "U32", 0x00)
+            .target_to_host_fifo("NumbersFromFPGA", "U16", 0x01)
+            .target_to_host_fifo("NumbersChannel", "U8", 0x02)
+            .build()
+            .registers;
+
+        generate_fifo_module(&registers, &GeneratorOptions::default());
+    }
 }
@@ -8,9 +8,10 @@ pub struct StringConstantVisitor {
 }
 
 impl StringConstantVisitor {
-    pub fn new(prefix: &str, suffix: &str) -> Self {
+    /// Create the visitor for the constant named `{header_prefix}{prefix}_{suffix}`.
+    pub fn new(header_prefix: &str, prefix: &str, suffix: &str) -> Self {
         Self {
-            name: format!("NiFpga_{prefix}_{suffix}"),
+            name: format!("{header_prefix}{prefix}_{suffix}"),
             value: None,
         }
     }
@@ -67,6 +68,7 @@ impl<'ast> Visit<'ast> for StringConstantVisitor {
 #[cfg(test)]
 mod tests {
     use super::StringConstantVisitor;
+    use crate::DEFAULT_HEADER_PREFIX;
 
     use lang_c::driver::{parse_preprocessed, Config};
     use lang_c::visit::Visit;
@@ -84,7 +86,7 @@ mod tests {
 
         "#;
 
-        let mut visitor = StringConstantVisitor::new("Main", "Signature");
+        let mut visitor = StringConstantVisitor::new(DEFAULT_HEADER_PREFIX, "Main", "Signature");
         visit_c_code(content, &mut visitor);
 
         assert_eq!(&visitor.value.unwrap(), "E3E0C23C5F01C0DBA61D947AB8A8F489");
@@ -98,7 +100,7 @@ mod tests {
 
         "#;
 
-        let mut visitor = StringConstantVisitor::new("Main", "Signature");
+        let mut visitor = StringConstantVisitor::new(DEFAULT_HEADER_PREFIX, "Main", "Signature");
         visit_c_code(content, &mut visitor);
 
         assert_eq!(visitor.value, None);
@@ -111,7 +113,7 @@ mod tests {
 
         "#;
 
-        let mut visitor = StringConstantVisitor::new("Main", "Signature");
+        let mut visitor = StringConstantVisitor::new(DEFAULT_HEADER_PREFIX, "Main", "Signature");
         visit_c_code(content, &mut visitor);
 
         assert_eq!(visitor.value, None);
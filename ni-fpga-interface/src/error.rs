@@ -1,18 +1,138 @@
 //! Error support for the FPGA Interface.
 
+use alloc::string::String;
+
+use crate::session::RegisterAddress;
+use crate::types::IrqSelection;
+
 #[derive(Debug)]
 pub enum FPGAError {
     InternalError(NiFpgaStatus),
     ContextAlreadyActive,
+    /// The context couldn't be finalized as other sessions or clones of it are still alive.
+    ContextInUse,
+    /// The FPGA returned a value which doesn't match any variant of the enum.
+    /// This contains the raw value that was read.
+    InvalidEnumValue(i128),
+    /// The FPGA was asked to run when it is already running.
+    AlreadyRunning,
+    /// These IRQs were still asserted after they were acknowledged.
+    IrqStillAsserted(IrqSelection),
+    /// A register didn't have the expected value.
+    /// The values are formatted with [`Debug`] so this can hold any register type.
+    AssertionFailed {
+        register_addr: RegisterAddress,
+        expected: String,
+        actual: String,
+    },
 }
 
 pub type Result<T> = core::result::Result<T, FPGAError>;
 
-impl FPGAError {}
+impl FPGAError {
+    /// A process exit code for the category of the error, for command line tools.
+    ///
+    /// | Code | Category      | Examples                                                       |
+    /// |------|---------------|----------------------------------------------------------------|
+    /// | 1    | Other         | Assertion failures, invalid enum values, unexpected errors     |
+    /// | 2    | Timeout       | FIFO or IRQ timeouts                                           |
+    /// | 3    | Resource      | Resource not found, out of memory, FPGA busy, context in use   |
+    /// | 4    | Communication | The target can't be reached or the connection failed          |
+    /// | 5    | Bitfile       | Invalid bitfile, signature or NI-RIO version mismatch          |
+    ///
+    /// ```no_run
+    /// # fn run() -> Result<(), ni_fpga_interface::error::FPGAError> { Ok(()) }
+    /// if let Err(error) = run() {
+    ///     eprintln!("{error}");
+    ///     std::process::exit(error.exit_code());
+    /// }
+    /// ```
+    pub fn exit_code(&self) -> i32 {
+        const OTHER: i32 = 1;
+        const TIMEOUT: i32 = 2;
+        const RESOURCE: i32 = 3;
+        const COMMUNICATION: i32 = 4;
+        const BITFILE: i32 = 5;
+
+        match self {
+            FPGAError::InternalError(status) => match *status {
+                NiFpgaStatus::FIFO_TIMEOUT | NiFpgaStatus::IRQ_TIMEOUT => TIMEOUT,
+                NiFpgaStatus::MEMORY_FULL
+                | NiFpgaStatus::RESOURCE_NOT_FOUND
+                | NiFpgaStatus::RESOURCE_NOT_INITIALIZED
+                | NiFpgaStatus::FPGA_BUSY
+                | NiFpgaStatus::FPGA_BUSY_FPGA_INTERFACE_C_API
+                | NiFpgaStatus::FPGA_BUSY_SCAN_INTERFACE
+                | NiFpgaStatus::FPGA_BUSY_FPGA_INTERFACE
+                | NiFpgaStatus::FPGA_BUSY_INTERACTIVE
+                | NiFpgaStatus::FPGA_BUSY_EMULATION
+                | NiFpgaStatus::FPGA_BUSY_CONFIGURATION
+                | NiFpgaStatus::TOTAL_DMA_FIFO_DEPTH_EXCEEDED
+                | NiFpgaStatus::FIFO_RESERVED => RESOURCE,
+                NiFpgaStatus::COMMUNICATION_TIMEOUT
+                | NiFpgaStatus::ACCESS_DENIED
+                | NiFpgaStatus::RPC_CONNECTION_ERROR
+                | NiFpgaStatus::RPC_SESSION_ERROR => COMMUNICATION,
+                NiFpgaStatus::DOWNLOAD_ERROR
+                | NiFpgaStatus::DEVICE_TYPE_MISMATCH
+                | NiFpgaStatus::CORRUPT_BITFILE
+                | NiFpgaStatus::HOST_VERSION_MISMATCH
+                | NiFpgaStatus::BITFILE_READ_ERROR
+                | NiFpgaStatus::SIGNATURE_MISMATCH
+                | NiFpgaStatus::INCOMPATIBLE_BITFILE
+                | NiFpgaStatus::VERSION_MISMATCH => BITFILE,
+                _ => OTHER,
+            },
+            FPGAError::ContextAlreadyActive
+            | FPGAError::ContextInUse
+            | FPGAError::AlreadyRunning => RESOURCE,
+            FPGAError::InvalidEnumValue(_)
+            | FPGAError::IrqStillAsserted(_)
+            | FPGAError::AssertionFailed { .. } => OTHER,
+        }
+    }
+}
+
+impl core::fmt::Display for FPGAError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FPGAError::InternalError(status) => write!(f, "{status}"),
+            FPGAError::ContextAlreadyActive => write!(f, "An NI FPGA context is already active."),
+            FPGAError::ContextInUse => write!(
+                f,
+                "The NI FPGA context is still in use by other sessions or references."
+            ),
+            FPGAError::InvalidEnumValue(value) => write!(
+                f,
+                "The FPGA returned {value} which is not a valid value for the enum."
+            ),
+            FPGAError::AlreadyRunning => write!(f, "{}", NiFpgaStatus::ALREADY_RUNNING),
+            FPGAError::IrqStillAsserted(irqs) => write!(
+                f,
+                "{irqs:?} still asserted after being acknowledged. The FPGA may be re-asserting them."
+            ),
+            FPGAError::AssertionFailed {
+                register_addr,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Register 0x{register_addr:X} was {actual} but expected {expected}."
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FPGAError {}
 
 impl From<NiFpgaStatus> for FPGAError {
     fn from(status: NiFpgaStatus) -> Self {
-        FPGAError::InternalError(status)
+        if status == NiFpgaStatus::ALREADY_RUNNING {
+            FPGAError::AlreadyRunning
+        } else {
+            FPGAError::InternalError(status)
+        }
     }
 }
 
@@ -26,11 +146,74 @@ pub fn to_fpga_result<T>(value: T, status: NiFpgaStatus) -> Result<T> {
 
 /// Wrapper for the status return from th NI FPGA library
 /// where the value represents the errors.
+///
+/// The default is success.
 #[repr(transparent)]
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
 pub struct NiFpgaStatus(i32);
 
 impl NiFpgaStatus {
+    /// No errors or warnings.
+    pub const SUCCESS: NiFpgaStatus = NiFpgaStatus(0);
+    /// The timeout expired before the FIFO operation could complete.
+    pub const FIFO_TIMEOUT: NiFpgaStatus = NiFpgaStatus(-50400);
+    /// A memory allocation failed.
+    pub const MEMORY_FULL: NiFpgaStatus = NiFpgaStatus(-52000);
+    /// An unexpected software error occurred.
+    pub const SOFTWARE_FAULT: NiFpgaStatus = NiFpgaStatus(-52003);
+    /// A required resource such as the NiFpga library or the RIO resource was not found.
+    pub const RESOURCE_NOT_FOUND: NiFpgaStatus = NiFpgaStatus(-52006);
+    /// A required resource was not initialized, such as a missing IRQ context.
+    pub const RESOURCE_NOT_INITIALIZED: NiFpgaStatus = NiFpgaStatus(-52010);
+    /// The FPGA is already running.
+    pub const ALREADY_RUNNING: NiFpgaStatus = NiFpgaStatus(-61003);
+    /// An error occurred downloading the VI to the FPGA.
+    pub const DOWNLOAD_ERROR: NiFpgaStatus = NiFpgaStatus(-61018);
+    /// The bitfile was not compiled for the device type of the resource.
+    pub const DEVICE_TYPE_MISMATCH: NiFpgaStatus = NiFpgaStatus(-61024);
+    /// Communication between the host and the FPGA target failed.
+    pub const COMMUNICATION_TIMEOUT: NiFpgaStatus = NiFpgaStatus(-61046);
+    /// The timeout expired before any of the IRQs were asserted.
+    pub const IRQ_TIMEOUT: NiFpgaStatus = NiFpgaStatus(-61060);
+    /// The bitfile is invalid or corrupt.
+    pub const CORRUPT_BITFILE: NiFpgaStatus = NiFpgaStatus(-61070);
+    /// The FPGA is busy.
+    pub const FPGA_BUSY: NiFpgaStatus = NiFpgaStatus(-61141);
+    /// The FPGA is busy in FPGA Interface C API mode.
+    pub const FPGA_BUSY_FPGA_INTERFACE_C_API: NiFpgaStatus = NiFpgaStatus(-61200);
+    /// The chassis is in Scan Interface programming mode.
+    pub const FPGA_BUSY_SCAN_INTERFACE: NiFpgaStatus = NiFpgaStatus(-61201);
+    /// The FPGA is busy in FPGA Interface mode.
+    pub const FPGA_BUSY_FPGA_INTERFACE: NiFpgaStatus = NiFpgaStatus(-61202);
+    /// The FPGA is busy in Interactive mode.
+    pub const FPGA_BUSY_INTERACTIVE: NiFpgaStatus = NiFpgaStatus(-61203);
+    /// The FPGA is busy in Emulation mode.
+    pub const FPGA_BUSY_EMULATION: NiFpgaStatus = NiFpgaStatus(-61204);
+    /// The FPGA is in configuration or discovery mode.
+    pub const FPGA_BUSY_CONFIGURATION: NiFpgaStatus = NiFpgaStatus(-61252);
+    /// The combined depth of the DMA FIFOs is more than the driver could allocate.
+    pub const TOTAL_DMA_FIFO_DEPTH_EXCEEDED: NiFpgaStatus = NiFpgaStatus(-63003);
+    /// Access to the remote system was denied.
+    pub const ACCESS_DENIED: NiFpgaStatus = NiFpgaStatus(-63033);
+    /// The NI-RIO software on the host is not compatible with the target.
+    pub const HOST_VERSION_MISMATCH: NiFpgaStatus = NiFpgaStatus(-63038);
+    /// A connection could not be established to the remote device.
+    pub const RPC_CONNECTION_ERROR: NiFpgaStatus = NiFpgaStatus(-63040);
+    /// The RPC session is invalid, the target may have reset.
+    pub const RPC_SESSION_ERROR: NiFpgaStatus = NiFpgaStatus(-63043);
+    /// Another session is accessing the FIFO.
+    pub const FIFO_RESERVED: NiFpgaStatus = NiFpgaStatus(-63082);
+    /// A valid .lvbitx bitfile is required.
+    pub const BITFILE_READ_ERROR: NiFpgaStatus = NiFpgaStatus(-63101);
+    /// The signature doesn't match the bitfile.
+    pub const SIGNATURE_MISMATCH: NiFpgaStatus = NiFpgaStatus(-63106);
+    /// The bitfile is incompatible with the installed NI-RIO version.
+    pub const INCOMPATIBLE_BITFILE: NiFpgaStatus = NiFpgaStatus(-63107);
+    /// The requested feature is not supported.
+    pub const FEATURE_NOT_SUPPORTED: NiFpgaStatus = NiFpgaStatus(-63193);
+    /// The NI-RIO software on the target is not compatible with this software.
+    pub const VERSION_MISMATCH: NiFpgaStatus = NiFpgaStatus(-63194);
+
     pub fn is_error(&self) -> bool {
         self.0 < 0
     }
@@ -93,14 +276,60 @@ impl NiFpgaStatus {
     }
 }
 
-impl std::fmt::Display for NiFpgaStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for NiFpgaStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{:?}: {}", self.0, self.get_error_description())
     }
 }
 
-impl std::fmt::Debug for NiFpgaStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for NiFpgaStatus {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{:?}: {}", self.0, self.get_error_description())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_already_running_status_maps_to_named_error() {
+        let error: FPGAError = NiFpgaStatus::ALREADY_RUNNING.into();
+        assert!(matches!(error, FPGAError::AlreadyRunning));
+    }
+
+    #[test]
+    fn test_exit_codes_by_category() {
+        assert_eq!(FPGAError::from(NiFpgaStatus::FIFO_TIMEOUT).exit_code(), 2);
+        assert_eq!(
+            FPGAError::from(NiFpgaStatus::RESOURCE_NOT_FOUND).exit_code(),
+            3
+        );
+        assert_eq!(
+            FPGAError::from(NiFpgaStatus::FPGA_BUSY_EMULATION).exit_code(),
+            3
+        );
+        assert_eq!(
+            FPGAError::from(NiFpgaStatus::RPC_CONNECTION_ERROR).exit_code(),
+            4
+        );
+        assert_eq!(
+            FPGAError::from(NiFpgaStatus::SIGNATURE_MISMATCH).exit_code(),
+            5
+        );
+        assert_eq!(
+            FPGAError::from(NiFpgaStatus::HOST_VERSION_MISMATCH).exit_code(),
+            5
+        );
+        assert_eq!(FPGAError::from(NiFpgaStatus(-52003)).exit_code(), 1);
+    }
+
+    #[test]
+    fn test_other_status_maps_to_internal_error() {
+        let error: FPGAError = NiFpgaStatus::FIFO_TIMEOUT.into();
+        assert!(matches!(
+            error,
+            FPGAError::InternalError(NiFpgaStatus::FIFO_TIMEOUT)
+        ));
+    }
+}
@@ -1,10 +1,18 @@
 //! Provides the high level interface for DMA FIFOs.
 
-use crate::error::FPGAError;
+use crate::error::{FPGAError, NiFpgaStatus};
 use crate::nifpga_sys::*;
-use crate::session::{FifoInterface, FifoReadRegion, FifoWriteRegion, NativeFpgaType, Session};
+use crate::session::{
+    FifoControlInterface, FifoInterface, FifoReadRegion, FifoWriteRegion, NativeFpgaType,
+};
+use std::collections::{BTreeMap, HashMap};
 use std::marker::PhantomData;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// The host buffer depth the driver uses when a FIFO hasn't been configured.
+///
+/// The driver will use twice the FPGA buffer size instead if that is larger.
+pub const DEFAULT_FIFO_DEPTH: usize = 10_000;
 
 /// The elements that are common between read and write FIFOs.
 pub trait Fifo {
@@ -27,7 +35,7 @@ pub trait Fifo {
     /// fifo.start(&session).unwrap();
     ///
     /// ```
-    fn start(&mut self, session: &Session) -> Result<(), FPGAError> {
+    fn start(&mut self, session: &impl FifoControlInterface) -> Result<(), FPGAError> {
         session.start_fifo(self.address())
     }
 
@@ -45,7 +53,7 @@ pub trait Fifo {
     /// fifo.stop(&session).unwrap();
     ///
     /// ```
-    fn stop(&mut self, session: &Session) -> Result<(), FPGAError> {
+    fn stop(&mut self, session: &impl FifoControlInterface) -> Result<(), FPGAError> {
         session.stop_fifo(self.address())
     }
 
@@ -53,7 +61,7 @@ pub trait Fifo {
     /// The new depth is implemented when the next FIFO Start, FIFO Read, or FIFO Write method executes.
     /// Before the new depth is set, the driver empties all data from the host memory and FPGA FIFO.
     ///
-    /// This method is optional as the buffer is set by default to 10000 elements or twice the size of the FPGA buffer size.
+    /// This method is optional as the buffer is set by default to [`DEFAULT_FIFO_DEPTH`] elements or twice the size of the FPGA buffer size.
     ///
     /// NI recommend this is set to 5 times the number of elements you specify to read and write.
     ///
@@ -72,22 +80,166 @@ pub trait Fifo {
     /// fifo.start(&session).unwrap();
     ///
     /// ```
-    fn configure(&mut self, session: &Session, requested_depth: usize) -> Result<usize, FPGAError> {
+    fn configure(
+        &mut self,
+        session: &impl FifoControlInterface,
+        requested_depth: usize,
+    ) -> Result<usize, FPGAError> {
         session.configure_fifo(self.address(), requested_depth)
     }
 
+    /// Returns the host buffer to the default depth after it has been changed with [`Fifo::configure`].
+    ///
+    /// This requests [`DEFAULT_FIFO_DEPTH`] elements. The driver can't report the default it would have chosen
+    /// so if the FPGA buffer is larger than half of this the depth applied may differ from the original default.
+    ///
+    /// Like [`Fifo::configure`] this returns the actual depth configured and applies on the next start, read or write.
+    ///
+    /// ```rust
+    /// # use ni_fpga_interface::fifos::{ ReadFifo, Fifo};
+    /// # use ni_fpga_interface::session::Session;
+    ///
+    ///
+    /// let session = Session::new("main.lvbitx", "sig", "RIO0").unwrap();
+    /// let mut fifo = ReadFifo::<u64>::new(1);
+    ///
+    /// fifo.configure(&session, 100_000).unwrap();
+    /// let depth = fifo.reset_configuration(&session).unwrap();
+    /// ```
+    fn reset_configuration(
+        &mut self,
+        session: &impl FifoControlInterface,
+    ) -> Result<usize, FPGAError> {
+        self.configure(session, DEFAULT_FIFO_DEPTH)
+    }
+
     fn get_peer_to_peer_fifo_endpoint(
         &self,
-        session: &Session,
+        session: &impl FifoControlInterface,
     ) -> Result<PeerToPeerEndpoint, FPGAError> {
         session.get_peer_to_peer_fifo_endpoint(self.address())
     }
 }
 
+/// The result of a [`ReadFifo::read_with_outcome`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FifoReadOutcome {
+    /// The whole buffer was filled.
+    Complete {
+        /// The elements left in the FIFO.
+        remaining: usize,
+    },
+    /// The timeout expired before the buffer could be filled.
+    Partial {
+        /// The number of valid elements at the start of the buffer.
+        read: usize,
+        /// The elements left in the FIFO.
+        remaining: usize,
+    },
+}
+
+impl FifoReadOutcome {
+    /// The number of elements read for a buffer of the requested size.
+    pub fn elements_read(&self, requested: usize) -> usize {
+        match self {
+            FifoReadOutcome::Complete { .. } => requested,
+            FifoReadOutcome::Partial { read, .. } => *read,
+        }
+    }
+
+    /// The number of elements left in the FIFO after the read.
+    pub fn remaining(&self) -> usize {
+        match self {
+            FifoReadOutcome::Complete { remaining }
+            | FifoReadOutcome::Partial { remaining, .. } => *remaining,
+        }
+    }
+}
+
+/// A snapshot of the host buffer of a FIFO.
+///
+/// The NI FPGA C API doesn't expose status flags for DMA FIFOs so there is no way to ask
+/// the driver whether it is started or whether data was lost. This is what can be seen from the
+/// host with a single zero element read or write. See [`ReadFifo::status`] and [`WriteFifo::status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FifoStatus {
+    /// The FIFO was stopped through this value with [`Fifo::stop`] and hasn't been started, read or written since.
+    ///
+    /// The FIFO isn't queried while it is stopped so the other fields report an empty buffer.
+    pub stopped: bool,
+    /// The elements available to read for a [`ReadFifo`] or the space available to write for a [`WriteFifo`].
+    pub available: usize,
+    /// The host buffer depth if it has been configured through the session.
+    pub depth: Option<usize>,
+    /// The host buffer has no free space.
+    ///
+    /// For a [`ReadFifo`] this needs the depth and is always false without it.
+    /// It doesn't mean data was lost, see [`ReadFifo::buffer_was_full`].
+    pub buffer_full: bool,
+    /// The host buffer holds no elements.
+    ///
+    /// For a [`WriteFifo`] this means the FPGA may be waiting for data.
+    /// It needs the depth and is always false without it.
+    pub buffer_empty: bool,
+}
+
+impl FifoStatus {
+    /// The status of a read FIFO where `available` is the elements in the buffer.
+    fn for_read(available: usize, depth: Option<usize>) -> Self {
+        Self {
+            stopped: false,
+            available,
+            depth,
+            buffer_full: depth.is_some_and(|depth| available >= depth),
+            buffer_empty: available == 0,
+        }
+    }
+
+    /// The status of a write FIFO where `available` is the free space in the buffer.
+    fn for_write(available: usize, depth: Option<usize>) -> Self {
+        Self {
+            stopped: false,
+            available,
+            depth,
+            buffer_full: available == 0,
+            buffer_empty: depth.is_some_and(|depth| available >= depth),
+        }
+    }
+
+    /// The status of a stopped FIFO. Stopping deletes the data so the buffer is empty.
+    fn for_stopped(depth: Option<usize>) -> Self {
+        Self {
+            stopped: true,
+            available: 0,
+            depth,
+            buffer_full: false,
+            buffer_empty: true,
+        }
+    }
+}
+
 /// A FIFO that can be read from.
+///
+/// This records whether a read has found the host buffer full, see [`ReadFifo::buffer_was_full`].
+/// The generated FIFOs are `pub const` values so copy one into a binding and use that binding.
+/// Calling a method on the const itself works on a temporary copy and the flag is lost.
+///
+/// ```rust
+/// # use ni_fpga_interface::fifos::ReadFifo;
+/// # use ni_fpga_interface::session::Session;
+/// # #[allow(non_upper_case_globals)]
+/// # const NumbersFromFPGA: ReadFifo<u64> = ReadFifo::new(1);
+/// let session = Session::new("main.lvbitx", "sig", "RIO0").unwrap();
+/// let mut fifo = NumbersFromFPGA;
+/// let mut buffer = [0u64; 10];
+/// fifo.read(&session, None, &mut buffer).unwrap();
+/// assert!(!fifo.buffer_was_full());
+/// ```
 pub struct ReadFifo<T: NativeFpgaType> {
     address: FifoAddress,
     phantom: PhantomData<T>,
+    buffer_was_full: bool,
+    stopped: bool,
 }
 
 // check the 'static - should never have a lifetime in this - can we remove it somehow?
@@ -96,6 +248,40 @@ impl<T: NativeFpgaType + 'static> ReadFifo<T> {
         Self {
             address,
             phantom: PhantomData,
+            buffer_was_full: false,
+            stopped: false,
+        }
+    }
+
+    /// Returns true if a read has found the host buffer full since this was created or last cleared.
+    ///
+    /// A full buffer doesn't mean data was lost. The NI FPGA C API doesn't provide overflow counters for DMA FIFOs
+    /// and no read returns an overflow status. Data is only lost if the FPGA's FIFO write times out while the
+    /// host buffer is full, so this is a sign it may have happened rather than proof.
+    /// If you need an exact count you should count the timeouts in the FPGA code.
+    ///
+    /// This can only be detected once the depth is known so it requires the FIFO to have been
+    /// configured through the session, e.g. with [`Fifo::configure`] or [`configure_all`].
+    pub fn buffer_was_full(&self) -> bool {
+        self.buffer_was_full
+    }
+
+    /// Clears the flag returned by [`ReadFifo::buffer_was_full`].
+    pub fn clear_buffer_was_full(&mut self) {
+        self.buffer_was_full = false;
+    }
+
+    /// Records if the host buffer was full when this read happened.
+    fn track_buffer_full(
+        &mut self,
+        session: &impl FifoInterface<T>,
+        elements_read: usize,
+        elements_remaining: usize,
+    ) {
+        if let Some(depth) = session.configured_depth(self.address) {
+            if elements_read + elements_remaining >= depth {
+                self.buffer_was_full = true;
+            }
         }
     }
 
@@ -124,7 +310,214 @@ impl<T: NativeFpgaType + 'static> ReadFifo<T> {
         timeout: Option<Duration>,
         data: &mut [T],
     ) -> Result<usize, FPGAError> {
-        session.read_fifo(self.address, data, timeout)
+        self.stopped = false;
+        let remaining = session.read_fifo(self.address, data, timeout)?;
+        self.track_buffer_full(session, data.len(), remaining);
+        Ok(remaining)
+    }
+
+    /// Read from the FIFO into the provided buffer, waiting no later than the deadline.
+    ///
+    /// This is useful in control loops which work to an absolute tick rather than a relative timeout.
+    /// If the deadline has already passed this polls the FIFO without waiting.
+    ///
+    /// If the buffer can't be filled before the deadline then whatever is available is read instead.
+    ///
+    /// Returns the number of elements read into the start of the buffer and the number of elements still to be read.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use ni_fpga_interface::fifos::{ ReadFifo, Fifo};
+    /// # use ni_fpga_interface::session::Session;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let session = Session::new("main.lvbitx", "sig", "RIO0").unwrap();
+    /// let mut fifo = ReadFifo::<u64>::new(1);
+    /// let mut buffer = [0u64; 10];
+    /// let next_tick = Instant::now() + Duration::from_millis(10);
+    /// let (read, remaining) = fifo.read_until(&session, &mut buffer, next_tick).unwrap();
+    /// ```
+    pub fn read_until(
+        &mut self,
+        session: &impl FifoInterface<T>,
+        data: &mut [T],
+        deadline: Instant,
+    ) -> Result<(usize, usize), FPGAError> {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let outcome = self.read_with_outcome(session, Some(timeout), data)?;
+        Ok((outcome.elements_read(data.len()), outcome.remaining()))
+    }
+
+    /// Read from the FIFO into the provided buffer, reporting whether the read completed.
+    ///
+    /// A FIFO read which times out transfers nothing, so [`ReadFifo::read`] returns an error and any
+    /// data which did arrive stays in the FIFO. This instead reads whatever is available when the timeout
+    /// expires and returns [`FifoReadOutcome::Partial`] with the number of valid elements at the start of the buffer.
+    /// If enough data arrived after the timeout to fill the buffer the outcome is [`FifoReadOutcome::Complete`].
+    ///
+    /// Any other failure is returned as an error.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use ni_fpga_interface::fifos::{ FifoReadOutcome, ReadFifo, Fifo};
+    /// # use ni_fpga_interface::session::Session;
+    /// use std::time::Duration;
+    ///
+    /// let session = Session::new("main.lvbitx", "sig", "RIO0").unwrap();
+    /// let mut fifo = ReadFifo::<u64>::new(1);
+    /// let mut buffer = [0u64; 10];
+    /// match fifo.read_with_outcome(&session, Some(Duration::from_millis(100)), &mut buffer).unwrap() {
+    ///     FifoReadOutcome::Complete { .. } => println!("{:?}", buffer),
+    ///     FifoReadOutcome::Partial { read, .. } => println!("{:?}", &buffer[..read]),
+    /// }
+    /// ```
+    pub fn read_with_outcome(
+        &mut self,
+        session: &impl FifoInterface<T>,
+        timeout: Option<Duration>,
+        data: &mut [T],
+    ) -> Result<FifoReadOutcome, FPGAError> {
+        self.stopped = false;
+        let outcome = match session.read_fifo(self.address, data, timeout) {
+            Ok(remaining) => FifoReadOutcome::Complete { remaining },
+            Err(FPGAError::InternalError(status)) if status == NiFpgaStatus::FIFO_TIMEOUT => {
+                // A timed out read transfers nothing so take what is there now instead.
+                let available = session.read_fifo(self.address, &mut [], Some(Duration::ZERO))?;
+                let read = available.min(data.len());
+                let remaining =
+                    session.read_fifo(self.address, &mut data[..read], Some(Duration::ZERO))?;
+                if read == data.len() {
+                    // Enough arrived after the timeout to fill the buffer anyway.
+                    FifoReadOutcome::Complete { remaining }
+                } else {
+                    FifoReadOutcome::Partial { read, remaining }
+                }
+            }
+            Err(error) => return Err(error),
+        };
+        self.track_buffer_full(
+            session,
+            outcome.elements_read(data.len()),
+            outcome.remaining(),
+        );
+        Ok(outcome)
+    }
+
+    /// Reads exactly `total` elements from the FIFO as an iterator.
+    ///
+    /// This is useful to capture a fixed number of samples and stop.
+    /// The elements are read from the FIFO in blocks of up to [`TAKE_BLOCK_SIZE`] elements
+    /// and the timeout applies to each block read.
+    ///
+    /// If the timeout expires before the elements arrive the iterator yields the error and then ends
+    /// so you will receive fewer than `total` elements.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use ni_fpga_interface::fifos::{ ReadFifo, Fifo};
+    /// # use ni_fpga_interface::session::Session;
+    /// use std::time::Duration;
+    ///
+    /// let session = Session::new("main.lvbitx", "sig", "RIO0").unwrap();
+    /// let mut fifo = ReadFifo::<u64>::new(1);
+    /// let samples = fifo
+    ///     .take(&session, 10_000, Some(Duration::from_millis(100)))
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// ```
+    pub fn take<'f, 's, S: FifoInterface<T>>(
+        &'f mut self,
+        session: &'s S,
+        total: usize,
+        timeout: Option<Duration>,
+    ) -> FifoTake<'f, 's, T, S>
+    where
+        T: Default,
+    {
+        FifoTake {
+            fifo: self,
+            session,
+            timeout,
+            remaining: total,
+            buffer: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Reads everything left in the FIFO and then stops it.
+    ///
+    /// Stopping a FIFO deletes any data in it so this is the safe way to finish an acquisition.
+    /// This keeps reading until the FIFO is empty so if the FPGA is still writing you should stop that first.
+    ///
+    /// If the timeout expires while there is still data in the FIFO this fails with a
+    /// [`NiFpgaStatus::FIFO_TIMEOUT`] error rather than deleting that data.
+    /// If reading or stopping the FIFO fails, or the timeout expires, the [`DrainError`] holds the data
+    /// read before the failure together with the error. The FIFO isn't stopped if a read fails or the
+    /// timeout expires so nothing more is deleted.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use ni_fpga_interface::fifos::{ ReadFifo, Fifo};
+    /// # use ni_fpga_interface::session::Session;
+    /// use std::time::Duration;
+    ///
+    /// let session = Session::new("main.lvbitx", "sig", "RIO0").unwrap();
+    /// let mut fifo = ReadFifo::<u64>::new(1);
+    /// match fifo.drain_and_stop(&session, Duration::from_millis(100)) {
+    ///     Ok(data) => println!("{:?}", data),
+    ///     Err(error) => eprintln!("{} after reading {:?}", error.error, error.data),
+    /// }
+    /// ```
+    pub fn drain_and_stop(
+        &mut self,
+        session: &(impl FifoInterface<T> + FifoControlInterface),
+        timeout: Duration,
+    ) -> Result<Vec<T>, DrainError<T>>
+    where
+        T: Default,
+    {
+        let mut data = Vec::new();
+        let result = self
+            .drain_into(session, Instant::now() + timeout, &mut data)
+            .and_then(|_| self.stop(session));
+        match result {
+            Ok(()) => Ok(data),
+            Err(error) => Err(DrainError { data, error }),
+        }
+    }
+
+    /// Read into `data` until the FIFO is empty.
+    ///
+    /// If the deadline passes with elements still available this returns a [`NiFpgaStatus::FIFO_TIMEOUT`] error.
+    /// On an error `data` keeps the elements read before it.
+    fn drain_into(
+        &mut self,
+        session: &impl FifoInterface<T>,
+        deadline: Instant,
+        data: &mut Vec<T>,
+    ) -> Result<(), FPGAError>
+    where
+        T: Default,
+    {
+        loop {
+            let available = self.elements_available(session)?;
+            if available == 0 {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(NiFpgaStatus::FIFO_TIMEOUT.into());
+            }
+            let start = data.len();
+            data.resize(start + available, T::default());
+            if let Err(error) = self.read(session, Some(Duration::ZERO), &mut data[start..]) {
+                data.truncate(start);
+                return Err(error);
+            }
+        }
     }
 
     /// Provides a mechanism to read from the FIFO without copying the data.
@@ -155,36 +548,154 @@ impl<T: NativeFpgaType + 'static> ReadFifo<T> {
         elements: usize,
         timeout: Option<Duration>,
     ) -> Result<(FifoReadRegion<'s, 'd, T>, usize), FPGAError> {
+        self.stopped = false;
         session.zero_copy_read(self.address, elements, timeout)
     }
 
     /// Returns the number of elements available to read.
     ///
     /// Warning: This achieves this by reading zero elements from the FIFO so it will start the FIFO if stopped.
+    /// Use [`ReadFifo::status`] if the FIFO may have been stopped.
     pub fn elements_available(&self, session: &impl FifoInterface<T>) -> Result<usize, FPGAError> {
         let mut empty_buffer: [T; 0] = [];
         session.read_fifo(self.address, &mut empty_buffer, None)
     }
+
+    /// Returns the state of the host buffer from a single query.
+    ///
+    /// The NI FPGA C API has no way to query a DMA FIFO without a transfer, so this reads zero elements
+    /// which would start a stopped FIFO. To avoid that this FIFO remembers when it was stopped through
+    /// [`Fifo::stop`] and reports [`FifoStatus::stopped`] without querying the driver until it is next started or read.
+    /// A stop through another copy of the FIFO or the session isn't seen.
+    ///
+    /// ```rust
+    /// # use ni_fpga_interface::fifos::ReadFifo;
+    /// # use ni_fpga_interface::session::Session;
+    ///
+    /// let session = Session::new("main.lvbitx", "sig", "RIO0").unwrap();
+    /// let fifo = ReadFifo::<u64>::new(1);
+    /// let status = fifo.status(&session).unwrap();
+    /// if status.buffer_full {
+    ///     println!("The host is not keeping up with the FPGA.");
+    /// }
+    /// ```
+    pub fn status(&self, session: &impl FifoInterface<T>) -> Result<FifoStatus, FPGAError> {
+        let depth = session.configured_depth(self.address);
+        if self.stopped {
+            return Ok(FifoStatus::for_stopped(depth));
+        }
+        let available = self.elements_available(session)?;
+        Ok(FifoStatus::for_read(available, depth))
+    }
+}
+
+/// The error from [`ReadFifo::drain_and_stop`] with the data read before it failed.
+#[derive(Debug)]
+pub struct DrainError<T> {
+    /// The data read from the FIFO before the error.
+    pub data: Vec<T>,
+    pub error: FPGAError,
+}
+
+impl<T> std::fmt::Display for DrainError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Draining the FIFO failed after reading {} element(s): {}",
+            self.data.len(),
+            self.error
+        )
+    }
+}
+
+impl<T: std::fmt::Debug> std::error::Error for DrainError<T> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// The largest number of elements [`ReadFifo::take`] will read from the FIFO at once.
+pub const TAKE_BLOCK_SIZE: usize = 4096;
+
+/// An iterator over a fixed number of elements from a [`ReadFifo`].
+///
+/// This is created by [`ReadFifo::take`].
+pub struct FifoTake<'f, 's, T: NativeFpgaType, S: FifoInterface<T>> {
+    fifo: &'f mut ReadFifo<T>,
+    session: &'s S,
+    timeout: Option<Duration>,
+    /// The elements still to be read from the FIFO.
+    remaining: usize,
+    buffer: Vec<T>,
+    position: usize,
+}
+
+impl<T: NativeFpgaType + Default + 'static, S: FifoInterface<T>> Iterator
+    for FifoTake<'_, '_, T, S>
+{
+    type Item = Result<T, FPGAError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == self.buffer.len() {
+            if self.remaining == 0 {
+                return None;
+            }
+            let block_size = self.remaining.min(TAKE_BLOCK_SIZE);
+            self.buffer.clear();
+            self.buffer.resize(block_size, T::default());
+            self.position = 0;
+            if let Err(error) = self.fifo.read(self.session, self.timeout, &mut self.buffer) {
+                // End after reporting the shortfall.
+                self.remaining = 0;
+                self.buffer.clear();
+                return Some(Err(error));
+            }
+            self.remaining -= block_size;
+        }
+
+        let value = self.buffer[self.position];
+        self.position += 1;
+        Some(Ok(value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = self.buffer.len() - self.position;
+        (0, Some(buffered + self.remaining))
+    }
 }
 
 impl<T: NativeFpgaType> Fifo for ReadFifo<T> {
     fn address(&self) -> FifoAddress {
         self.address
     }
+
+    fn start(&mut self, session: &impl FifoControlInterface) -> Result<(), FPGAError> {
+        session.start_fifo(self.address)?;
+        self.stopped = false;
+        Ok(())
+    }
+
+    fn stop(&mut self, session: &impl FifoControlInterface) -> Result<(), FPGAError> {
+        session.stop_fifo(self.address)?;
+        self.stopped = true;
+        Ok(())
+    }
 }
 
 /// A FIFO that can be written to.
 pub struct WriteFifo<T: NativeFpgaType> {
     address: FifoAddress,
     phantom: PhantomData<T>,
+    stopped: bool,
 }
 
 // see note on 'static above. should try to remove it.
 impl<T: NativeFpgaType + 'static> WriteFifo<T> {
-    pub const fn new(address: u32) -> Self {
+    pub const fn new(address: FifoAddress) -> Self {
         Self {
             address,
             phantom: PhantomData,
+            stopped: false,
         }
     }
 
@@ -212,6 +723,7 @@ impl<T: NativeFpgaType + 'static> WriteFifo<T> {
         timeout: Option<Duration>,
         data: &[T],
     ) -> Result<usize, FPGAError> {
+        self.stopped = false;
         session.write_fifo(self.address, data, timeout)
     }
 
@@ -245,19 +757,586 @@ impl<T: NativeFpgaType + 'static> WriteFifo<T> {
         elements: usize,
         timeout: Option<Duration>,
     ) -> Result<(FifoWriteRegion<'s, 'd, T>, usize), FPGAError> {
+        self.stopped = false;
         session.zero_copy_write(self.address, elements, timeout)
     }
 
     /// Returns the number of elements free to write.
     /// Warning: This achieves this by writing zero elements to the FIFO so it will start the FIFO if stopped.
+    /// Use [`WriteFifo::status`] if the FIFO may have been stopped.
     pub fn space_available(&self, session: &impl FifoInterface<T>) -> Result<usize, FPGAError> {
         let empty_buffer: [T; 0] = [];
         session.write_fifo(self.address, &empty_buffer, None)
     }
+
+    /// Returns the state of the host buffer from a single query.
+    ///
+    /// Like [`ReadFifo::status`] this doesn't query a FIFO stopped through [`Fifo::stop`] so it isn't restarted.
+    pub fn status(&self, session: &impl FifoInterface<T>) -> Result<FifoStatus, FPGAError> {
+        let depth = session.configured_depth(self.address);
+        if self.stopped {
+            return Ok(FifoStatus::for_stopped(depth));
+        }
+        let available = self.space_available(session)?;
+        Ok(FifoStatus::for_write(available, depth))
+    }
 }
 
 impl<T: NativeFpgaType> Fifo for WriteFifo<T> {
     fn address(&self) -> FifoAddress {
         self.address
     }
+
+    fn start(&mut self, session: &impl FifoControlInterface) -> Result<(), FPGAError> {
+        session.start_fifo(self.address)?;
+        self.stopped = false;
+        Ok(())
+    }
+
+    fn stop(&mut self, session: &impl FifoControlInterface) -> Result<(), FPGAError> {
+        session.stop_fifo(self.address)?;
+        self.stopped = true;
+        Ok(())
+    }
+}
+
+/// A pair of FIFOs used together as a bidirectional channel.
+///
+/// This is common in request/response designs where the host sends data to the FPGA on one FIFO
+/// and receives the results on another.
+///
+/// The build step generates these for FIFO pairs named `{Name}ToFPGA` and `{Name}FromFPGA`
+/// or you can construct one from any write and read FIFO.
+pub struct FifoChannel<W: NativeFpgaType, R: NativeFpgaType> {
+    write_fifo: WriteFifo<W>,
+    read_fifo: ReadFifo<R>,
+}
+
+impl<W: NativeFpgaType + 'static, R: NativeFpgaType + 'static> FifoChannel<W, R> {
+    pub const fn new(write_fifo: WriteFifo<W>, read_fifo: ReadFifo<R>) -> Self {
+        Self {
+            write_fifo,
+            read_fifo,
+        }
+    }
+
+    /// Send data to the FPGA on the write FIFO.
+    ///
+    /// See [`WriteFifo::write`] for details of the arguments.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use ni_fpga_interface::fifos::{ FifoChannel, ReadFifo, WriteFifo};
+    /// # use ni_fpga_interface::session::Session;
+    /// use std::time::Duration;
+    ///
+    /// let session = Session::new("main.lvbitx", "sig", "RIO0").unwrap();
+    /// let mut channel = FifoChannel::new(WriteFifo::<u32>::new(0), ReadFifo::<u16>::new(1));
+    /// channel.send(&session, Some(Duration::from_millis(100)), &[1, 2, 3]).unwrap();
+    /// let mut response = [0u16; 3];
+    /// channel.recv(&session, Some(Duration::from_millis(100)), &mut response).unwrap();
+    /// ```
+    pub fn send(
+        &mut self,
+        session: &impl FifoInterface<W>,
+        timeout: Option<Duration>,
+        data: &[W],
+    ) -> Result<usize, FPGAError> {
+        self.write_fifo.write(session, timeout, data)
+    }
+
+    /// Receive data from the FPGA on the read FIFO.
+    ///
+    /// See [`ReadFifo::read`] for details of the arguments.
+    pub fn recv(
+        &mut self,
+        session: &impl FifoInterface<R>,
+        timeout: Option<Duration>,
+        data: &mut [R],
+    ) -> Result<usize, FPGAError> {
+        self.read_fifo.read(session, timeout, data)
+    }
+
+    /// Access the write FIFO, for example to configure or start it.
+    pub fn write_fifo(&mut self) -> &mut WriteFifo<W> {
+        &mut self.write_fifo
+    }
+
+    /// Access the read FIFO, for example to configure or start it.
+    pub fn read_fifo(&mut self) -> &mut ReadFifo<R> {
+        &mut self.read_fifo
+    }
+}
+
+/// The requested host buffer depths for a set of FIFOs, keyed by the FIFO name.
+///
+/// FIFOs without an entry are configured with [`DEFAULT_FIFO_DEPTH`].
+///
+/// # Example
+///
+/// ```rust
+/// use ni_fpga_interface::fifos::FifoPlan;
+///
+/// let plan = FifoPlan::new()
+///     .depth("NumbersFromFPGA", 100_000)
+///     .depth("NumbersToFPGA", 1024);
+/// assert_eq!(plan.requested_depth("NumbersToFPGA"), 1024);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FifoPlan {
+    depths: HashMap<String, usize>,
+}
+
+impl FifoPlan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request a host buffer depth for the named FIFO.
+    pub fn depth(mut self, fifo: impl Into<String>, depth: usize) -> Self {
+        self.depths.insert(fifo.into(), depth);
+        self
+    }
+
+    /// The depth requested for the named FIFO.
+    pub fn requested_depth(&self, fifo: &str) -> usize {
+        self.depths.get(fifo).copied().unwrap_or(DEFAULT_FIFO_DEPTH)
+    }
+
+    /// The names in the plan which aren't one of the FIFOs, in name order.
+    fn unknown_fifos(&self, fifos: &[(&'static str, FifoAddress)]) -> Vec<String> {
+        let mut unknown: Vec<String> = self
+            .depths
+            .keys()
+            .filter(|name| !fifos.iter().any(|(fifo, _)| fifo == name))
+            .cloned()
+            .collect();
+        unknown.sort();
+        unknown
+    }
+}
+
+/// The actual depths configured for each FIFO, keyed by the FIFO name.
+pub type FifoDepths = BTreeMap<&'static str, usize>;
+
+/// The errors from setting up FIFOs with [`configure_all`].
+///
+/// This includes the depths of the FIFOs that were set up successfully.
+#[derive(Debug)]
+pub struct FifoPlanError {
+    pub depths: FifoDepths,
+    pub errors: Vec<(&'static str, FPGAError)>,
+    /// Names in the plan which don't match any of the FIFOs. Nothing is configured if there are any.
+    pub unknown_fifos: Vec<String>,
+}
+
+impl std::fmt::Display for FifoPlanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.unknown_fifos.is_empty() {
+            return write!(
+                f,
+                "The FIFO plan names FIFO(s) which aren't in the interface: {}.",
+                self.unknown_fifos.join(", ")
+            );
+        }
+        write!(f, "Failed to set up {} FIFO(s):", self.errors.len())?;
+        for (name, error) in &self.errors {
+            write!(f, " {name}: {error};")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FifoPlanError {}
+
+/// Configure and start each of the FIFOs with the depth requested in the plan.
+///
+/// The generated `fifos` module provides a `configure_all` which passes all the FIFOs in the interface.
+///
+/// Each FIFO is configured through [`FifoControlInterface::configure_fifo`] so the session records the depths
+/// in the same way as [`Fifo::configure`].
+///
+/// If the plan names a FIFO which isn't in `fifos`, e.g. because of a typo, nothing is configured and the
+/// names are returned in [`FifoPlanError::unknown_fifos`].
+///
+/// Otherwise all the FIFOs are attempted even if one fails. If any fail the errors are returned together
+/// with the depths of the FIFOs which succeeded.
+pub fn configure_all(
+    session: &impl FifoControlInterface,
+    fifos: &[(&'static str, FifoAddress)],
+    plan: &FifoPlan,
+) -> Result<FifoDepths, FifoPlanError> {
+    let mut depths = FifoDepths::new();
+    let mut errors = Vec::new();
+
+    let unknown_fifos = plan.unknown_fifos(fifos);
+    if !unknown_fifos.is_empty() {
+        return Err(FifoPlanError {
+            depths,
+            errors,
+            unknown_fifos,
+        });
+    }
+
+    for &(name, address) in fifos {
+        let result = session
+            .configure_fifo(address, plan.requested_depth(name))
+            .and_then(|depth| session.start_fifo(address).map(|_| depth));
+        match result {
+            Ok(depth) => {
+                depths.insert(name, depth);
+            }
+            Err(error) => errors.push((name, error)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(depths)
+    } else {
+        Err(FifoPlanError {
+            depths,
+            errors,
+            unknown_fifos: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockSession, FAILING_FIFO};
+
+    #[test]
+    fn test_read_until_fills_buffer() {
+        let mock = MockSession::with_elements(6);
+        let mut fifo = ReadFifo::<u32>::new(1);
+        let mut buffer = [0u32; 4];
+        let deadline = Instant::now() + Duration::from_secs(10);
+
+        let result = fifo.read_until(&mock, &mut buffer, deadline).unwrap();
+
+        assert_eq!(result, (4, 2));
+        assert_eq!(buffer, [0, 1, 2, 3]);
+        assert!(mock.timeouts.borrow()[0].unwrap() > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_read_until_returns_partial_read_on_timeout() {
+        let mock = MockSession::with_elements(3);
+        let mut fifo = ReadFifo::<u32>::new(1);
+        let mut buffer = [0u32; 5];
+
+        let result = fifo.read_until(&mock, &mut buffer, Instant::now()).unwrap();
+
+        assert_eq!(result, (3, 0));
+        assert_eq!(buffer, [0, 1, 2, 0, 0]);
+    }
+
+    #[test]
+    fn test_read_until_past_deadline_polls() {
+        let mock = MockSession::with_elements(4);
+        let mut fifo = ReadFifo::<u32>::new(1);
+        let mut buffer = [0u32; 2];
+        let deadline = Instant::now() - Duration::from_millis(5);
+
+        fifo.read_until(&mock, &mut buffer, deadline).unwrap();
+
+        assert_eq!(mock.timeouts.borrow()[0], Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_channel_receives_from_read_fifo() {
+        let mock = MockSession::with_elements(3);
+        let mut channel = FifoChannel::new(WriteFifo::<u32>::new(0), ReadFifo::<u32>::new(1));
+        let mut buffer = [0u32; 2];
+
+        let remaining = channel.recv(&mock, None, &mut buffer).unwrap();
+
+        assert_eq!(remaining, 1);
+        assert_eq!(buffer, [0, 1]);
+    }
+
+    #[test]
+    fn test_buffer_was_full_flagged_when_buffer_full() {
+        let mock = MockSession::with_elements(8);
+        let mut fifo = ReadFifo::<u32>::new(1);
+        fifo.configure(&mock, 8).unwrap();
+        let mut buffer = [0u32; 2];
+
+        fifo.read(&mock, None, &mut buffer).unwrap();
+        assert!(fifo.buffer_was_full());
+
+        fifo.clear_buffer_was_full();
+        fifo.read(&mock, None, &mut buffer).unwrap();
+        assert!(!fifo.buffer_was_full());
+    }
+
+    #[test]
+    fn test_buffer_was_full_not_flagged_without_depth() {
+        let mock = MockSession::with_elements(10);
+        let mut fifo = ReadFifo::<u32>::new(1);
+        let mut buffer = [0u32; 2];
+
+        fifo.read(&mock, None, &mut buffer).unwrap();
+
+        assert!(!fifo.buffer_was_full());
+    }
+
+    #[test]
+    fn test_depth_configured_on_another_copy_is_used() {
+        const FIFO: ReadFifo<u32> = ReadFifo::new(1);
+        let mock = MockSession::with_elements(4);
+        let mut configured = FIFO;
+        configured.configure(&mock, 4).unwrap();
+
+        let mut fifo = FIFO;
+        let mut buffer = [0u32; 1];
+        fifo.read(&mock, None, &mut buffer).unwrap();
+
+        assert!(fifo.buffer_was_full());
+    }
+
+    #[test]
+    fn test_read_status_reports_full_buffer() {
+        let mock = MockSession::with_elements(4);
+        let mut fifo = ReadFifo::<u32>::new(1);
+
+        let status = fifo.status(&mock).unwrap();
+        assert_eq!(status.available, 4);
+        assert_eq!(status.depth, None);
+        assert!(!status.buffer_full);
+
+        fifo.configure(&mock, 4).unwrap();
+        let status = fifo.status(&mock).unwrap();
+        assert_eq!(status.depth, Some(4));
+        assert!(status.buffer_full);
+        assert!(!status.buffer_empty);
+    }
+
+    #[test]
+    fn test_status_doesnt_restart_stopped_fifo() {
+        let mock = MockSession::with_elements(4);
+        let mut fifo = ReadFifo::<u32>::new(1);
+        fifo.stop(&mock).unwrap();
+
+        let status = fifo.status(&mock).unwrap();
+        assert!(status.stopped);
+        assert_eq!(status.available, 0);
+        assert!(mock.timeouts.borrow().is_empty());
+
+        fifo.start(&mock).unwrap();
+        let status = fifo.status(&mock).unwrap();
+        assert!(!status.stopped);
+        assert_eq!(status.available, 4);
+    }
+
+    #[test]
+    fn test_write_status_reports_empty_buffer() {
+        let mock = MockSession::default();
+        mock.write_space.set(8);
+        let mut fifo = WriteFifo::<u32>::new(2);
+        fifo.configure(&mock, 8).unwrap();
+
+        let status = fifo.status(&mock).unwrap();
+        assert!(status.buffer_empty);
+        assert!(!status.buffer_full);
+
+        fifo.write(&mock, None, &[0; 8]).unwrap();
+        let status = fifo.status(&mock).unwrap();
+        assert_eq!(status.available, 0);
+        assert!(status.buffer_full);
+        assert!(!status.buffer_empty);
+    }
+
+    #[test]
+    fn test_take_reads_exact_total_across_blocks() {
+        let total = TAKE_BLOCK_SIZE as u32 + 10;
+        let mock = MockSession::with_elements(total + 5);
+        let mut fifo = ReadFifo::<u32>::new(1);
+
+        let samples: Vec<u32> = fifo
+            .take(&mock, total as usize, None)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(samples, (0..total).collect::<Vec<_>>());
+        assert_eq!(mock.elements.borrow().len(), 5);
+    }
+
+    #[test]
+    fn test_take_yields_error_on_shortfall() {
+        let mock = MockSession::with_elements(3);
+        let mut fifo = ReadFifo::<u32>::new(1);
+
+        let results: Vec<_> = fifo.take(&mock, 5, Some(Duration::ZERO)).collect();
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
+
+    #[test]
+    fn test_drain_reads_until_empty() {
+        let mock = MockSession::with_elements(7);
+        let mut fifo = ReadFifo::<u32>::new(1);
+
+        let mut data = Vec::new();
+        fifo.drain_into(&mock, Instant::now() + Duration::from_secs(10), &mut data)
+            .unwrap();
+
+        assert_eq!(data, (0..7).collect::<Vec<_>>());
+        assert!(mock.elements.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_drain_and_stop_stops_fifo() {
+        let mock = MockSession::with_elements(3);
+        let mut fifo = ReadFifo::<u32>::new(1);
+
+        let data = fifo.drain_and_stop(&mock, Duration::from_secs(10)).unwrap();
+
+        assert_eq!(data, vec![0, 1, 2]);
+        assert!(mock.stopped.get());
+    }
+
+    #[test]
+    fn test_drain_and_stop_reports_timeout_with_data_left() {
+        let mock = MockSession::with_elements(3);
+        let mut fifo = ReadFifo::<u32>::new(1);
+
+        let error = fifo.drain_and_stop(&mock, Duration::ZERO).unwrap_err();
+
+        assert!(error.data.is_empty());
+        assert!(matches!(
+            error.error,
+            FPGAError::InternalError(NiFpgaStatus::FIFO_TIMEOUT)
+        ));
+        // The data left is kept in the FIFO rather than deleted by a stop.
+        assert!(!mock.stopped.get());
+        assert_eq!(mock.elements.borrow().len(), 3);
+    }
+
+    #[test]
+    fn test_drain_and_stop_keeps_data_when_stop_fails() {
+        let mock = MockSession::with_elements(3);
+        let mut fifo = ReadFifo::<u32>::new(FAILING_FIFO);
+
+        let error = fifo
+            .drain_and_stop(&mock, Duration::from_secs(10))
+            .unwrap_err();
+
+        assert_eq!(error.data, vec![0, 1, 2]);
+        assert!(matches!(
+            error.error,
+            FPGAError::InternalError(NiFpgaStatus::FIFO_RESERVED)
+        ));
+    }
+
+    #[test]
+    fn test_configure_through_control_interface_records_depth() {
+        let mock = MockSession::default();
+        let mut fifo = ReadFifo::<u32>::new(1);
+
+        assert_eq!(fifo.configure(&mock, 3).unwrap(), 4);
+        mock.elements.borrow_mut().extend(0..4);
+        let mut buffer = [0u32; 4];
+        fifo.read(&mock, None, &mut buffer).unwrap();
+
+        assert!(fifo.buffer_was_full());
+    }
+
+    #[test]
+    fn test_read_with_outcome_complete() {
+        let mock = MockSession::with_elements(5);
+        let mut fifo = ReadFifo::<u32>::new(1);
+        let mut buffer = [0u32; 3];
+
+        let outcome = fifo.read_with_outcome(&mock, None, &mut buffer).unwrap();
+
+        assert_eq!(outcome, FifoReadOutcome::Complete { remaining: 2 });
+        assert_eq!(outcome.elements_read(buffer.len()), 3);
+    }
+
+    #[test]
+    fn test_read_with_outcome_partial_on_timeout() {
+        let mock = MockSession::with_elements(2);
+        let mut fifo = ReadFifo::<u32>::new(1);
+        let mut buffer = [0u32; 3];
+
+        let outcome = fifo
+            .read_with_outcome(&mock, Some(Duration::ZERO), &mut buffer)
+            .unwrap();
+
+        assert_eq!(
+            outcome,
+            FifoReadOutcome::Partial {
+                read: 2,
+                remaining: 0
+            }
+        );
+        assert_eq!(buffer, [0, 1, 0]);
+    }
+
+    #[test]
+    fn test_read_with_outcome_complete_when_data_arrives_after_timeout() {
+        let mock = MockSession::with_elements(2);
+        mock.late_elements.borrow_mut().push_back(2);
+        let mut fifo = ReadFifo::<u32>::new(1);
+        let mut buffer = [0u32; 3];
+
+        let outcome = fifo
+            .read_with_outcome(&mock, Some(Duration::ZERO), &mut buffer)
+            .unwrap();
+
+        assert_eq!(outcome, FifoReadOutcome::Complete { remaining: 0 });
+        assert_eq!(buffer, [0, 1, 2]);
+    }
+
+    #[test]
+    fn test_configure_all_returns_actual_depths() {
+        let mock = MockSession::default();
+        let plan = FifoPlan::new().depth("Input", 1000);
+
+        let depths = configure_all(&mock, &[("Input", 1), ("Output", 2)], &plan).unwrap();
+
+        assert_eq!(depths["Input"], 1024);
+        assert_eq!(depths["Output"], DEFAULT_FIFO_DEPTH.next_power_of_two());
+        assert_eq!(*mock.started.borrow(), vec![1, 2]);
+        assert_eq!(FifoInterface::<u32>::configured_depth(&mock, 1), Some(1024));
+    }
+
+    #[test]
+    fn test_configure_all_continues_after_error() {
+        let mock = MockSession::default();
+
+        let error = configure_all(
+            &mock,
+            &[("Broken", FAILING_FIFO), ("Output", 2)],
+            &FifoPlan::new(),
+        )
+        .unwrap_err();
+
+        assert_eq!(error.errors.len(), 1);
+        assert_eq!(error.errors[0].0, "Broken");
+        assert!(matches!(
+            error.errors[0].1,
+            FPGAError::InternalError(NiFpgaStatus::FIFO_RESERVED)
+        ));
+        assert!(error.depths.contains_key("Output"));
+    }
+
+    #[test]
+    fn test_configure_all_rejects_unknown_plan_names() {
+        let mock = MockSession::default();
+        let plan = FifoPlan::new().depth("Inptu", 1000);
+
+        let error = configure_all(&mock, &[("Input", 1)], &plan).unwrap_err();
+
+        assert_eq!(error.unknown_fifos, vec!["Inptu".to_string()]);
+        assert!(mock.started.borrow().is_empty());
+        assert_eq!(
+            error.to_string(),
+            "The FIFO plan names FIFO(s) which aren't in the interface: Inptu."
+        );
+    }
 }
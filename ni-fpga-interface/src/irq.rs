@@ -1,8 +1,12 @@
 //! The IRQ module implements handling for interrupts to and from the FPGA.
 
-use std::{fmt::Debug, time::Duration};
+use core::time::Duration;
+#[cfg(all(feature = "std", debug_assertions))]
+use std::sync::{Mutex, PoisonError};
 
-use crate::{error::FPGAError, nifpga_sys::*, session::Session, types::FpgaBool};
+use crate::error::FPGAError;
+#[cfg(feature = "std")]
+use crate::{nifpga_sys::*, session::Session, types::FpgaBool};
 
 // Re-excport the IRQ selection types from here for a better dev experience.
 pub use crate::types::IrqSelection;
@@ -46,9 +50,49 @@ pub const IRQ31: IrqSelection = IrqSelection::new(31);
 /// This should be created from the session using [`Session::create_irq_context`].
 ///
 /// The context is unreserved when it is dropped.
+///
+/// In debug builds the session tracks which IRQs any of its contexts have reported as asserted.
+/// Acknowledging an IRQ which hasn't been reported since it was last acknowledged prints a warning
+/// to stderr, as acknowledging a fixed mask regardless of what fired can drop a genuine IRQ.
+/// The acknowledge still goes ahead as it is valid for the driver.
+#[cfg(feature = "std")]
 pub struct IrqContext<'session> {
     handle: IrqContextHandle,
-    session: &'session SessionHandle,
+    session: &'session Session,
+}
+
+/// The IRQs the contexts of a session have reported as asserted which haven't been acknowledged since.
+///
+/// This is held by the session so an IRQ waited on by one context can be acknowledged through
+/// another context or the session itself.
+#[cfg(all(feature = "std", debug_assertions))]
+#[derive(Debug, Default)]
+pub(crate) struct PendingIrqs(Mutex<IrqSelection>);
+
+#[cfg(all(feature = "std", debug_assertions))]
+impl PendingIrqs {
+    fn record_asserted(&self, irqs: IrqSelection) {
+        let mut pending = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        *pending = pending.union(irqs);
+    }
+
+    /// Remove the IRQs from the pending set and return any which weren't pending.
+    fn acknowledge(&self, irqs: IrqSelection) -> IrqSelection {
+        let mut pending = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let unexpected = irqs.without(*pending);
+        *pending = pending.without(irqs);
+        unexpected
+    }
+
+    /// Remove the IRQs from the pending set and warn about any which weren't pending.
+    fn acknowledge_with_warning(&self, irqs: IrqSelection) {
+        let unexpected = self.acknowledge(irqs);
+        if unexpected != IrqSelection::NONE {
+            eprintln!(
+                "Warning: acknowledging {unexpected:?} which have not been reported as asserted since they were last acknowledged."
+            );
+        }
+    }
 }
 
 /// Represents the result of a wait on IRQs.
@@ -60,6 +104,37 @@ pub enum IrqWaitResult {
     IrqsAsserted(IrqSelection),
 }
 
+/// The IRQ operations needed by code that waits on the FPGA.
+///
+/// [`IrqContext`] implements this against the driver but code written against the trait
+/// can be tested with a mock implementation.
+pub trait IrqInterface {
+    /// Wait on the specified IRQs for the specified timeout.
+    fn wait_on_irq(
+        &mut self,
+        irq: IrqSelection,
+        timeout: Duration,
+    ) -> Result<IrqWaitResult, FPGAError>;
+
+    /// Acknowledge the specified IRQs.
+    fn acknowledge_irqs(&self, irqs: IrqSelection) -> Result<(), FPGAError>;
+
+    /// Acknowledge the specified IRQs and check they are no longer asserted.
+    ///
+    /// This polls the IRQs with a zero timeout after acknowledging them and returns
+    /// [`FPGAError::IrqStillAsserted`] with any that are still set.
+    fn acknowledge_and_verify(&mut self, irqs: IrqSelection) -> Result<(), FPGAError> {
+        self.acknowledge_irqs(irqs)?;
+        match self.wait_on_irq(irqs, Duration::ZERO)? {
+            IrqWaitResult::IrqsAsserted(asserted) if asserted != IrqSelection::NONE => {
+                Err(FPGAError::IrqStillAsserted(asserted))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
 impl<'session> IrqContext<'session> {
     /// Wait on the specified IRQs for the specified timeout.
     ///
@@ -74,7 +149,7 @@ impl<'session> IrqContext<'session> {
 
         unsafe {
             let status = NiFpga_WaitOnIrqs(
-                *self.session,
+                self.session.handle,
                 self.handle,
                 irq,
                 timeout.as_millis() as u32,
@@ -90,19 +165,45 @@ impl<'session> IrqContext<'session> {
         if timed_out == FpgaBool::TRUE {
             Ok(IrqWaitResult::TimedOut)
         } else {
+            #[cfg(debug_assertions)]
+            self.session.pending_irqs.record_asserted(irqs_asserted);
             Ok(IrqWaitResult::IrqsAsserted(irqs_asserted))
         }
     }
+
+    /// Acknowledge the specified IRQs.
+    ///
+    /// This is the same as [`Session::acknowledge_irqs`], including the debug build warning.
+    pub fn acknowledge_irqs(&self, irqs: IrqSelection) -> Result<(), FPGAError> {
+        self.session.acknowledge_irqs(irqs)
+    }
+}
+
+#[cfg(feature = "std")]
+impl IrqInterface for IrqContext<'_> {
+    fn wait_on_irq(
+        &mut self,
+        irq: IrqSelection,
+        timeout: Duration,
+    ) -> Result<IrqWaitResult, FPGAError> {
+        IrqContext::wait_on_irq(self, irq, timeout)
+    }
+
+    fn acknowledge_irqs(&self, irqs: IrqSelection) -> Result<(), FPGAError> {
+        IrqContext::acknowledge_irqs(self, irqs)
+    }
 }
 
+#[cfg(feature = "std")]
 impl Drop for IrqContext<'_> {
     fn drop(&mut self) {
         unsafe {
-            NiFpga_UnreserveIrqContext(*self.session, self.handle);
+            NiFpga_UnreserveIrqContext(self.session.handle, self.handle);
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl Session {
     /// Creates an IRQ Context for the session.
     ///
@@ -112,7 +213,7 @@ impl Session {
     ///
     /// To minimize jitter when first waiting on IRQs, reserve as many contexts as the application requires.
     pub fn create_irq_context(&self) -> Result<IrqContext, FPGAError> {
-        let mut handle: IrqContextHandle = std::ptr::null();
+        let mut handle: IrqContextHandle = core::ptr::null();
         unsafe {
             let status = NiFpga_ReserveIrqContext(self.handle, &mut handle);
 
@@ -122,15 +223,33 @@ impl Session {
         }
         Ok(IrqContext {
             handle,
-            session: &self.handle,
+            session: self,
         })
     }
 
+    /// Acknowledge the specified IRQs and check they are no longer asserted using the context.
+    ///
+    /// This catches an FPGA which keeps re-asserting an IRQ, which would otherwise leave a
+    /// loop waiting on the IRQ spinning forever. See [`IrqInterface::acknowledge_and_verify`].
+    ///
+    /// The context should be one created from this session, e.g. with [`Session::create_irq_context`].
+    pub fn acknowledge_and_verify(
+        &self,
+        irqs: IrqSelection,
+        context: &mut impl IrqInterface,
+    ) -> Result<(), FPGAError> {
+        context.acknowledge_and_verify(irqs)
+    }
+
     /// Acknowledge the specified IRQs. See [`IrqSelection`] for details on setting specific IRQs.
+    ///
+    /// In debug builds this prints a warning if any of the IRQs haven't been reported as asserted
+    /// by a wait on one of this session's contexts since they were last acknowledged. See [`IrqContext`].
     pub fn acknowledge_irqs(&self, irqs: IrqSelection) -> Result<(), FPGAError> {
+        #[cfg(debug_assertions)]
+        self.pending_irqs.acknowledge_with_warning(irqs);
         unsafe {
             let status = NiFpga_AcknowledgeIrqs(self.handle, irqs);
-
             if status.is_error() {
                 return Err(status.into());
             }
@@ -138,3 +257,51 @@ impl Session {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSession;
+
+    #[test]
+    fn test_acknowledge_and_verify_passes_when_cleared() {
+        let mut irqs = MockSession::with_irqs(IRQ1, IrqSelection::NONE);
+        assert!(irqs.acknowledge_and_verify(IRQ1).is_ok());
+    }
+
+    #[test]
+    fn test_acknowledge_and_verify_reports_reasserted_irq() {
+        let mut irqs = MockSession::with_irqs(IRQ1, IRQ1);
+        let error = irqs.acknowledge_and_verify(IRQ1).unwrap_err();
+        assert!(matches!(error, FPGAError::IrqStillAsserted(irq) if irq == IRQ1));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_pending_irqs_acknowledge_asserted() {
+        let pending = PendingIrqs::default();
+        pending.record_asserted(IRQ1.union(IRQ2));
+        assert_eq!(pending.acknowledge(IRQ1), IrqSelection::NONE);
+        assert_eq!(pending.acknowledge(IRQ2), IrqSelection::NONE);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_pending_irqs_reports_double_acknowledge() {
+        let pending = PendingIrqs::default();
+        pending.record_asserted(IRQ1);
+        assert_eq!(pending.acknowledge(IRQ1), IrqSelection::NONE);
+        assert_eq!(pending.acknowledge(IRQ1), IRQ1);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_pending_irqs_reports_only_unasserted() {
+        let pending = PendingIrqs::default();
+        pending.record_asserted(IRQ1);
+        assert_eq!(pending.acknowledge(IRQ1.union(IRQ2)), IRQ2);
+        // IRQ1 was still cleared so it isn't reported again once re-asserted.
+        pending.record_asserted(IRQ1);
+        assert_eq!(pending.acknowledge(IRQ1), IrqSelection::NONE);
+    }
+}
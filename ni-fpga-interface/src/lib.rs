@@ -14,10 +14,27 @@
 //!
 //! Registers and FIFOs are dynamic according to the particular bitfile you load.
 //! For this reason, the build module generates a module with the definitions of the registers and FIFOs for you.
+//!
+//! # `no_std`
+//!
+//! The crate can be built without `std` by disabling the default `std` feature. It still needs `alloc`.
+//!
+//! Without `std` the [`registers`] types, the [`session::RegisterInterface`] trait and the shared IRQ types
+//! like [`irq::IrqSelection`] are available, so bindings generated with `no_std` enabled in the build crate compile.
+//! [`session::Session`], the [`fifos`] module and [`irq::IrqContext`] need `std`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
-mod error;
+pub mod error;
+#[cfg(feature = "std")]
 pub mod fifos;
 pub mod irq;
+#[cfg(test)]
+mod mock;
+// Without std only the address types are used.
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
 mod nifpga_sys;
 pub mod registers;
 pub mod session;
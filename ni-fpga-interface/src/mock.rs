@@ -0,0 +1,210 @@
+//! A mock backend for the unit tests so they can run without the driver.
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use crate::error::{NiFpgaStatus, Result};
+use crate::irq::{IrqInterface, IrqSelection, IrqWaitResult};
+use crate::nifpga_sys::PeerToPeerEndpoint;
+use crate::session::{
+    FifoAddress, FifoControlInterface, FifoInterface, FifoReadRegion, FifoWriteRegion,
+    RegisterAddress, RegisterInterface,
+};
+
+/// A FIFO address which the mock fails to start or stop.
+pub const FAILING_FIFO: FifoAddress = 99;
+
+/// Implements the driver interfaces against in memory state.
+///
+/// * Registers hold the values set with [`MockSession::with_register`] and fail to read otherwise.
+/// * FIFOs read `u32` elements from a single queue and time out if there aren't enough,
+///   after which any late elements arrive.
+/// * IRQs are asserted until acknowledged, unless they are sticky.
+#[derive(Default)]
+pub struct MockSession {
+    pub registers: RefCell<HashMap<RegisterAddress, u64>>,
+    pub reads: RefCell<Vec<RegisterAddress>>,
+    pub elements: RefCell<VecDeque<u32>>,
+    /// Elements which arrive in the FIFO when a read times out.
+    pub late_elements: RefCell<VecDeque<u32>>,
+    pub timeouts: RefCell<Vec<Option<Duration>>>,
+    pub written: RefCell<Vec<u32>>,
+    pub write_space: Cell<usize>,
+    pub depths: RefCell<HashMap<FifoAddress, usize>>,
+    pub started: RefCell<Vec<FifoAddress>>,
+    pub stopped: Cell<bool>,
+    pub asserted: Cell<IrqSelection>,
+    pub sticky: IrqSelection,
+}
+
+impl MockSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_register(self, address: RegisterAddress, value: u64) -> Self {
+        self.registers.borrow_mut().insert(address, value);
+        self
+    }
+
+    pub fn with_elements(count: u32) -> Self {
+        Self {
+            elements: RefCell::new((0..count).collect()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_irqs(asserted: IrqSelection, sticky: IrqSelection) -> Self {
+        Self {
+            asserted: Cell::new(asserted),
+            sticky,
+            ..Default::default()
+        }
+    }
+
+    fn unsupported<T>() -> Result<T> {
+        Err(NiFpgaStatus::FEATURE_NOT_SUPPORTED.into())
+    }
+}
+
+macro_rules! impl_mock_register_interface {
+    ($rust_type:ty) => {
+        impl RegisterInterface<$rust_type> for MockSession {
+            fn read(&self, address: RegisterAddress) -> Result<$rust_type> {
+                self.reads.borrow_mut().push(address);
+                match self.registers.borrow().get(&address) {
+                    Some(&value) => Ok(value as $rust_type),
+                    None => Err(NiFpgaStatus::RESOURCE_NOT_FOUND.into()),
+                }
+            }
+            fn write(&self, address: RegisterAddress, data: $rust_type) -> Result<()> {
+                self.registers.borrow_mut().insert(address, data as u64);
+                Ok(())
+            }
+            fn read_array_mut<const N: usize>(
+                &self,
+                address: RegisterAddress,
+                array: &mut [$rust_type; N],
+            ) -> Result<()> {
+                self.read_array_slice(address, array)
+            }
+            fn read_array_slice(
+                &self,
+                address: RegisterAddress,
+                array: &mut [$rust_type],
+            ) -> Result<()> {
+                let value = RegisterInterface::<$rust_type>::read(self, address)?;
+                array.fill(value);
+                Ok(())
+            }
+            fn write_array<const N: usize>(
+                &self,
+                address: RegisterAddress,
+                data: &[$rust_type; N],
+            ) -> Result<()> {
+                match data.first() {
+                    Some(&value) => RegisterInterface::<$rust_type>::write(self, address, value),
+                    None => Ok(()),
+                }
+            }
+        }
+    };
+}
+
+impl_mock_register_interface!(u8);
+impl_mock_register_interface!(u32);
+impl_mock_register_interface!(i16);
+
+impl FifoInterface<u32> for MockSession {
+    fn read_fifo(
+        &self,
+        _fifo: FifoAddress,
+        buffer: &mut [u32],
+        timeout: Option<Duration>,
+    ) -> Result<usize> {
+        self.timeouts.borrow_mut().push(timeout);
+        let mut elements = self.elements.borrow_mut();
+        if buffer.len() > elements.len() {
+            elements.append(&mut self.late_elements.borrow_mut());
+            return Err(NiFpgaStatus::FIFO_TIMEOUT.into());
+        }
+        for value in buffer.iter_mut() {
+            *value = elements.pop_front().unwrap();
+        }
+        Ok(elements.len())
+    }
+    fn write_fifo(
+        &self,
+        _fifo: FifoAddress,
+        data: &[u32],
+        _timeout: Option<Duration>,
+    ) -> Result<usize> {
+        let space = self.write_space.get();
+        if data.len() > space {
+            return Err(NiFpgaStatus::FIFO_TIMEOUT.into());
+        }
+        self.written.borrow_mut().extend_from_slice(data);
+        self.write_space.set(space - data.len());
+        Ok(self.write_space.get())
+    }
+    fn zero_copy_read(
+        &self,
+        _fifo: FifoAddress,
+        _elements: usize,
+        _timeout: Option<Duration>,
+    ) -> Result<(FifoReadRegion<'_, '_, u32>, usize)> {
+        Self::unsupported()
+    }
+    fn zero_copy_write(
+        &self,
+        _fifo: FifoAddress,
+        _elements: usize,
+        _timeout: Option<Duration>,
+    ) -> Result<(FifoWriteRegion<'_, '_, u32>, usize)> {
+        Self::unsupported()
+    }
+    fn configured_depth(&self, fifo: FifoAddress) -> Option<usize> {
+        self.depths.borrow().get(&fifo).copied()
+    }
+}
+
+impl FifoControlInterface for MockSession {
+    fn configure_fifo(&self, fifo: FifoAddress, requested_depth: usize) -> Result<usize> {
+        // Round up like the driver can to check the actual depth is reported.
+        let depth = requested_depth.next_power_of_two();
+        self.depths.borrow_mut().insert(fifo, depth);
+        Ok(depth)
+    }
+    fn start_fifo(&self, fifo: FifoAddress) -> Result<()> {
+        if fifo == FAILING_FIFO {
+            return Err(NiFpgaStatus::FIFO_RESERVED.into());
+        }
+        self.started.borrow_mut().push(fifo);
+        Ok(())
+    }
+    fn stop_fifo(&self, fifo: FifoAddress) -> Result<()> {
+        if fifo == FAILING_FIFO {
+            return Err(NiFpgaStatus::FIFO_RESERVED.into());
+        }
+        self.stopped.set(true);
+        Ok(())
+    }
+    fn get_peer_to_peer_fifo_endpoint(&self, _fifo: FifoAddress) -> Result<PeerToPeerEndpoint> {
+        Self::unsupported()
+    }
+}
+
+impl IrqInterface for MockSession {
+    fn wait_on_irq(&mut self, _irq: IrqSelection, _timeout: Duration) -> Result<IrqWaitResult> {
+        match self.asserted.get() {
+            IrqSelection::NONE => Ok(IrqWaitResult::TimedOut),
+            asserted => Ok(IrqWaitResult::IrqsAsserted(asserted)),
+        }
+    }
+
+    fn acknowledge_irqs(&self, _irqs: IrqSelection) -> Result<()> {
+        self.asserted.set(self.sticky);
+        Ok(())
+    }
+}
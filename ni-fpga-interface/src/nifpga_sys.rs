@@ -5,7 +5,12 @@ use paste::paste;
 
 pub type SessionHandle = u32;
 
-pub type FifoAddress = u32;
+/// The integer type the C API uses for register and FIFO addresses.
+///
+/// Everything else uses [`RegisterAddress`] or [`FifoAddress`] so this is the only place to change it.
+pub type Address = u32;
+pub type RegisterAddress = Address;
+pub type FifoAddress = Address;
 pub type PeerToPeerEndpoint = u32;
 
 pub type IrqContextHandle = *const c_void;
@@ -14,14 +19,14 @@ pub type IrqContextHandle = *const c_void;
 macro_rules! impl_type_session_interface {
     ($rust_type:ty, $fpga_type:literal) => {
 
-            paste! { pub fn [<NiFpga_Read $fpga_type >](session: SessionHandle, offset: u32, value: *mut $rust_type) -> NiFpgaStatus; }
-            paste! { pub fn [<NiFpga_Write $fpga_type >](session: SessionHandle, offset: u32, value: $rust_type) -> NiFpgaStatus; }
-            paste! { pub fn [<NiFpga_ReadArray $fpga_type >](session: SessionHandle, offset: u32, value: *mut $rust_type, size: size_t) -> NiFpgaStatus; }
-            paste! { pub fn [<NiFpga_WriteArray $fpga_type >](session: SessionHandle, offset: u32, value: *const $rust_type, size: size_t) -> NiFpgaStatus; }
-            paste! { pub fn [<NiFpga_ReadFifo $fpga_type >](session: SessionHandle, fifo: u32, data: *mut $rust_type, number_of_elements: size_t, timeout_ms: FpgaTimeoutMs, elements_remaining: *mut size_t) -> NiFpgaStatus; }
-            paste! { pub fn [<NiFpga_WriteFifo $fpga_type >](session: SessionHandle, fifo: u32, data: *const $rust_type, number_of_elements: size_t, timeout_ms: FpgaTimeoutMs, elements_remaining: *mut size_t) -> NiFpgaStatus;}
-            paste! { pub fn [<NiFpga_AcquireFifoReadElements $fpga_type >](session: SessionHandle, fifo: u32, elements: *mut *const $rust_type, elements_requested: size_t, timeout_ms: FpgaTimeoutMs, elements_acquired: *mut size_t, elements_remaining: *mut size_t) -> NiFpgaStatus; }
-            paste! { pub fn [<NiFpga_AcquireFifoWriteElements $fpga_type >](session: SessionHandle, fifo: u32, elements: *mut *mut $rust_type, elements_requested: size_t, timeout_ms: FpgaTimeoutMs, elements_acquired: *mut size_t, elements_remaining: *mut size_t) -> NiFpgaStatus; }
+            paste! { pub fn [<NiFpga_Read $fpga_type >](session: SessionHandle, offset: RegisterAddress, value: *mut $rust_type) -> NiFpgaStatus; }
+            paste! { pub fn [<NiFpga_Write $fpga_type >](session: SessionHandle, offset: RegisterAddress, value: $rust_type) -> NiFpgaStatus; }
+            paste! { pub fn [<NiFpga_ReadArray $fpga_type >](session: SessionHandle, offset: RegisterAddress, value: *mut $rust_type, size: size_t) -> NiFpgaStatus; }
+            paste! { pub fn [<NiFpga_WriteArray $fpga_type >](session: SessionHandle, offset: RegisterAddress, value: *const $rust_type, size: size_t) -> NiFpgaStatus; }
+            paste! { pub fn [<NiFpga_ReadFifo $fpga_type >](session: SessionHandle, fifo: FifoAddress, data: *mut $rust_type, number_of_elements: size_t, timeout_ms: FpgaTimeoutMs, elements_remaining: *mut size_t) -> NiFpgaStatus; }
+            paste! { pub fn [<NiFpga_WriteFifo $fpga_type >](session: SessionHandle, fifo: FifoAddress, data: *const $rust_type, number_of_elements: size_t, timeout_ms: FpgaTimeoutMs, elements_remaining: *mut size_t) -> NiFpgaStatus;}
+            paste! { pub fn [<NiFpga_AcquireFifoReadElements $fpga_type >](session: SessionHandle, fifo: FifoAddress, elements: *mut *const $rust_type, elements_requested: size_t, timeout_ms: FpgaTimeoutMs, elements_acquired: *mut size_t, elements_remaining: *mut size_t) -> NiFpgaStatus; }
+            paste! { pub fn [<NiFpga_AcquireFifoWriteElements $fpga_type >](session: SessionHandle, fifo: FifoAddress, elements: *mut *mut $rust_type, elements_requested: size_t, timeout_ms: FpgaTimeoutMs, elements_acquired: *mut size_t, elements_remaining: *mut size_t) -> NiFpgaStatus; }
     }
 }
 
@@ -1,22 +1,44 @@
 //! Implements the register interfaces to the FPGA.
 //!
+//! # Ordering
+//!
+//! Every register access is a call into the NI FPGA library. The compiler can't see
+//! into these calls so it will not reorder them relative to each other and they
+//! reach the FPGA in program order from a single thread.
+//!
+//! There is no guarantee of coherence between registers though. Each read is a
+//! separate transaction so the FPGA may update a value between two reads.
+//!
+//! If the order of reads matters (e.g. reading a count before a data snapshot) you can use
+//! [`crate::session::RegisterInterface::read_sequence`] to make that explicit.
+//! If you need ordering against memory shared with other threads, see [`crate::session::Session::barrier`].
 
-use crate::error::Result;
+use crate::error::{FPGAError, NiFpgaStatus, Result};
 use crate::session::{RegisterAddress, RegisterInterface};
+use alloc::{format, vec, vec::Vec};
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+/// Whether a register is a control or an indicator on the FPGA VI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterKind {
+    Control,
+    Indicator,
+}
 
 /// Provides a binding to a register address including a type.
 ///
 /// By generating these as part of an initialisation step - the registers can then be accessed safely at later steps knowing the address and types are matched.
 pub struct Register<T> {
     address: RegisterAddress,
-    phantom: std::marker::PhantomData<T>,
+    phantom: core::marker::PhantomData<T>,
 }
 
 impl<T: Default + Copy> Register<T> {
     pub const fn new(address: RegisterAddress) -> Self {
         Self {
             address,
-            phantom: std::marker::PhantomData,
+            phantom: core::marker::PhantomData,
         }
     }
 
@@ -24,21 +46,99 @@ impl<T: Default + Copy> Register<T> {
         session.read(self.address)
     }
 
+    /// Read the register without converting the status to a [`Result`].
+    ///
+    /// See [`RegisterInterface::read_raw`] for when to use this.
+    pub fn read_raw(&self, session: &impl RegisterInterface<T>) -> (T, NiFpgaStatus) {
+        session.read_raw(self.address)
+    }
+
     pub fn write(&self, session: &impl RegisterInterface<T>, value: T) -> Result<()> {
         session.write(self.address, value)
     }
+
+    /// Read the register and capture the host time immediately after the read completes.
+    ///
+    /// Use this to align FPGA values with a host clock when the FPGA can't timestamp them itself.
+    #[cfg(feature = "std")]
+    pub fn read_timestamped(&self, session: &impl RegisterInterface<T>) -> Result<(T, Instant)> {
+        let value = self.read(session)?;
+        Ok((value, Instant::now()))
+    }
+
+    /// Read the register and capture the host time before and after the read.
+    ///
+    /// The FPGA value was sampled somewhere between the two so this bounds the latency
+    /// of the read for jitter analysis.
+    #[cfg(feature = "std")]
+    pub fn read_bracketed(&self, session: &impl RegisterInterface<T>) -> Result<BracketedRead<T>> {
+        let started = Instant::now();
+        let value = self.read(session)?;
+        let completed = Instant::now();
+        Ok(BracketedRead {
+            value,
+            started,
+            completed,
+        })
+    }
+
+    /// Read the register and check it has the expected value.
+    ///
+    /// This is intended for hardware tests. On a mismatch it returns [`FPGAError::AssertionFailed`]
+    /// which includes the register address so the failure says which register was wrong.
+    pub fn assert_eq(&self, session: &impl RegisterInterface<T>, expected: T) -> Result<()>
+    where
+        T: PartialEq + core::fmt::Debug,
+    {
+        let actual = self.read(session)?;
+        if actual == expected {
+            Ok(())
+        } else {
+            Err(FPGAError::AssertionFailed {
+                register_addr: self.address,
+                expected: format!("{expected:?}"),
+                actual: format!("{actual:?}"),
+            })
+        }
+    }
+}
+
+/// A register value with the host times either side of the read.
+///
+/// Returned by [`Register::read_bracketed`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BracketedRead<T> {
+    pub value: T,
+    /// The time just before the read was started.
+    pub started: Instant,
+    /// The time just after the read completed.
+    pub completed: Instant,
+}
+
+#[cfg(feature = "std")]
+impl<T> BracketedRead<T> {
+    /// The upper bound on how long the read took.
+    pub fn latency(&self) -> Duration {
+        self.completed.duration_since(self.started)
+    }
+
+    /// The midpoint of the read which is the best estimate of when the value was sampled.
+    pub fn midpoint(&self) -> Instant {
+        self.started + self.latency() / 2
+    }
 }
 
 pub struct ArrayRegister<T, const N: usize> {
     address: RegisterAddress,
-    phantom_type: std::marker::PhantomData<T>,
+    phantom_type: core::marker::PhantomData<T>,
 }
 
 impl<T: Default + Copy, const N: usize> ArrayRegister<T, N> {
     pub const fn new(address: RegisterAddress) -> Self {
         Self {
             address,
-            phantom_type: std::marker::PhantomData,
+            phantom_type: core::marker::PhantomData,
         }
     }
 
@@ -49,6 +149,150 @@ impl<T: Default + Copy, const N: usize> ArrayRegister<T, N> {
     pub fn write(&self, session: &impl RegisterInterface<T>, value: &[T; N]) -> Result<()> {
         session.write_array(self.address, value)
     }
+
+    /// Access the array with a runtime size so it can be read onto the heap.
+    ///
+    /// See [`DynArrayRegister`].
+    pub const fn as_dyn(&self) -> DynArrayRegister<T> {
+        DynArrayRegister::new(self.address, N)
+    }
+}
+
+/// An array register where the size is set at runtime.
+///
+/// This reads into a [`Vec`] so very large arrays don't need to fit on the stack.
+pub struct DynArrayRegister<T> {
+    address: RegisterAddress,
+    size: usize,
+    phantom_type: core::marker::PhantomData<T>,
+}
+
+impl<T: Default + Copy> DynArrayRegister<T> {
+    pub const fn new(address: RegisterAddress, size: usize) -> Self {
+        Self {
+            address,
+            size,
+            phantom_type: core::marker::PhantomData,
+        }
+    }
+
+    /// The number of elements in the array.
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn read(&self, session: &impl RegisterInterface<T>) -> Result<Vec<T>> {
+        let mut values = vec![T::default(); self.size];
+        session.read_array_slice(self.address, &mut values)?;
+        Ok(values)
+    }
+
+    /// Read the array as an iterator over chunks of `chunk_size` elements.
+    ///
+    /// This is lazy so nothing is read until the first chunk is requested. The C API has no way
+    /// to address part of an array register so that first chunk reads the whole array into a heap
+    /// buffer with a single call and the chunks are copied out of it. If the read fails the iterator
+    /// yields the error and then ends. The last chunk is shorter if the size isn't a multiple of `chunk_size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn read_chunks<'a>(
+        &'a self,
+        session: &'a impl RegisterInterface<T>,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = Result<Vec<T>>> + 'a {
+        assert!(chunk_size > 0, "chunk_size must be greater than 0");
+        let mut values: Option<Vec<T>> = None;
+        let mut position = 0;
+        let mut failed = false;
+        core::iter::from_fn(move || {
+            if failed {
+                return None;
+            }
+            if values.is_none() {
+                match self.read(session) {
+                    Ok(read) => values = Some(read),
+                    Err(error) => {
+                        failed = true;
+                        return Some(Err(error));
+                    }
+                }
+            }
+            let values = values.as_ref()?;
+            if position >= values.len() {
+                return None;
+            }
+            let end = values.len().min(position + chunk_size);
+            let chunk = values[position..end].to_vec();
+            position = end;
+            Some(Ok(chunk))
+        })
+    }
+}
+
+/// A register holding a LabVIEW enum.
+///
+/// The C API exports enums as their underlying integer type `R` so this
+/// converts to and from your own Rust enum `E`. `R` can be any signed or unsigned integer.
+///
+/// Reads use [`TryFrom`] so if the FPGA reports a value which isn't
+/// a variant you get [`FPGAError::InvalidEnumValue`] with the raw value.
+pub struct EnumRegister<E, R> {
+    address: RegisterAddress,
+    phantom: core::marker::PhantomData<(E, R)>,
+}
+
+impl<E, R: Default + Copy + Into<i128>> EnumRegister<E, R> {
+    pub const fn new(address: RegisterAddress) -> Self {
+        Self {
+            address,
+            phantom: core::marker::PhantomData,
+        }
+    }
+
+    pub fn read(&self, session: &impl RegisterInterface<R>) -> Result<E>
+    where
+        E: TryFrom<R>,
+    {
+        let raw = session.read(self.address)?;
+        E::try_from(raw).map_err(|_| FPGAError::InvalidEnumValue(raw.into()))
+    }
+
+    pub fn write(&self, session: &impl RegisterInterface<R>, value: E) -> Result<()>
+    where
+        E: Into<R>,
+    {
+        session.write(self.address, value.into())
+    }
+}
+
+/// The fixed point format of an FXP control or indicator.
+///
+/// The generated module lists these for each FXP register in `FXP_INFO`
+/// so tools can format the values correctly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FxpTypeInfo {
+    pub signed: bool,
+    /// The total number of bits.
+    pub word_length: u8,
+    /// The number of bits before the binary point. This can be negative or larger than the word length.
+    pub integer_word_length: i16,
+}
+
+impl FxpTypeInfo {
+    pub const fn new(signed: bool, word_length: u8, integer_word_length: i16) -> Self {
+        Self {
+            signed,
+            word_length,
+            integer_word_length,
+        }
+    }
+
+    /// The number of bits after the binary point.
+    pub const fn fraction_bits(&self) -> i16 {
+        self.word_length as i16 - self.integer_word_length
+    }
 }
 
 /// Used to allow the implementation of clusters.
@@ -58,3 +302,164 @@ trait CustomRegisterType<const S: usize> {
     fn from_buffer(buffer: &[u8; S]) -> Self;
     fn to_buffer(&self, buffer: &mut [u8; S]);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSession;
+
+    #[derive(Debug, PartialEq)]
+    enum State {
+        Idle,
+        Running,
+    }
+
+    impl TryFrom<u8> for State {
+        type Error = ();
+
+        fn try_from(value: u8) -> std::result::Result<Self, Self::Error> {
+            match value {
+                0 => Ok(State::Idle),
+                1 => Ok(State::Running),
+                _ => Err(()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_enum_register_read_valid_value() {
+        let register = EnumRegister::<State, u8>::new(0x18002);
+        assert_eq!(
+            register
+                .read(&MockSession::new().with_register(0x18002, 1))
+                .unwrap(),
+            State::Running
+        );
+    }
+
+    #[test]
+    fn test_enum_register_read_invalid_value_includes_raw() {
+        let register = EnumRegister::<State, u8>::new(0x18002);
+        let error = register
+            .read(&MockSession::new().with_register(0x18002, 7))
+            .unwrap_err();
+        assert!(matches!(error, FPGAError::InvalidEnumValue(7)));
+        assert_eq!(
+            error.to_string(),
+            "The FPGA returned 7 which is not a valid value for the enum."
+        );
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Direction {
+        Reverse,
+        Forward,
+    }
+
+    impl TryFrom<i16> for Direction {
+        type Error = ();
+
+        fn try_from(value: i16) -> std::result::Result<Self, Self::Error> {
+            match value {
+                -1 => Ok(Direction::Reverse),
+                1 => Ok(Direction::Forward),
+                _ => Err(()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_enum_register_signed_backing() {
+        let register = EnumRegister::<Direction, i16>::new(0x18002);
+        let session = MockSession::new().with_register(0x18002, -1i16 as u64);
+        assert_eq!(register.read(&session).unwrap(), Direction::Reverse);
+
+        let session = MockSession::new().with_register(0x18002, -2i16 as u64);
+        let error = register.read(&session).unwrap_err();
+        assert!(matches!(error, FPGAError::InvalidEnumValue(-2)));
+    }
+
+    #[test]
+    fn test_register_assert_eq_names_register() {
+        let register = Register::<u8>::new(0x18002);
+        assert!(register
+            .assert_eq(&MockSession::new().with_register(0x18002, 4), 4)
+            .is_ok());
+
+        let error = register
+            .assert_eq(&MockSession::new().with_register(0x18002, 4), 5)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Register 0x18002 was 4 but expected 5.");
+    }
+
+    #[test]
+    fn test_register_read_bracketed_orders_timestamps() {
+        let register = Register::<u8>::new(0x18002);
+        let before = Instant::now();
+        let (value, timestamp) = register
+            .read_timestamped(&MockSession::new().with_register(0x18002, 2))
+            .unwrap();
+        assert_eq!(value, 2);
+        assert!(timestamp >= before);
+
+        let read = register
+            .read_bracketed(&MockSession::new().with_register(0x18002, 3))
+            .unwrap();
+        assert_eq!(read.value, 3);
+        assert!(read.started <= read.midpoint());
+        assert!(read.midpoint() <= read.completed);
+        assert_eq!(read.latency(), read.completed - read.started);
+    }
+
+    #[test]
+    fn test_fxp_fraction_bits() {
+        assert_eq!(FxpTypeInfo::new(true, 32, 16).fraction_bits(), 16);
+        assert_eq!(FxpTypeInfo::new(false, 8, 10).fraction_bits(), -2);
+    }
+
+    #[test]
+    fn test_dyn_array_read_chunks_covers_array() {
+        let register = DynArrayRegister::<u8>::new(0x18010, 10);
+        let session = MockSession::new().with_register(0x18010, 3);
+
+        let chunks = register.read_chunks(&session, 4);
+        // Nothing is read until the first chunk is requested.
+        assert!(session.reads.borrow().is_empty());
+        let chunks: Vec<Vec<u8>> = chunks.collect::<Result<_>>().unwrap();
+
+        let sizes: Vec<usize> = chunks.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![4, 4, 2]);
+        assert!(chunks.iter().flatten().all(|&value| value == 3));
+        assert_eq!(session.reads.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_dyn_array_read_chunks_yields_error_then_ends() {
+        let register = DynArrayRegister::<u8>::new(0x18010, 10);
+        let session = MockSession::new();
+
+        let mut chunks = register.read_chunks(&session, 4);
+
+        assert!(matches!(
+            chunks.next(),
+            Some(Err(FPGAError::InternalError(
+                NiFpgaStatus::RESOURCE_NOT_FOUND
+            )))
+        ));
+        assert!(chunks.next().is_none());
+    }
+
+    #[test]
+    fn test_array_register_as_dyn_keeps_size() {
+        let register = ArrayRegister::<u8, 2048>::new(0x18010);
+        assert_eq!(register.as_dyn().size(), 2048);
+        assert_eq!(
+            register
+                .as_dyn()
+                .read(&MockSession::new().with_register(0x18010, 1))
+                .unwrap()
+                .len(),
+            2048
+        );
+    }
+}
@@ -5,33 +5,104 @@
 //! * Registers which are the front panel controls and indicators of the FPGA VI.
 //! * FIFOs which are the DMA FIFOs of the FPGA VI.
 
-use crate::error::{to_fpga_result, Result};
+#[cfg(feature = "std")]
+use crate::error::to_fpga_result;
+use crate::error::{FPGAError, NiFpgaStatus, Result};
+#[cfg(feature = "std")]
 use crate::nifpga_sys::*;
+#[cfg(feature = "std")]
 use crate::session::Session;
 use crate::types::FpgaBool;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use libc::size_t;
+#[cfg(feature = "std")]
 use paste::paste;
+#[cfg(feature = "std")]
 use std::time::Duration;
 
 /// Marker trait for the types that are supported directly by the FPGA interface.
 pub trait NativeFpgaType: Copy {}
 
-pub type RegisterAddress = u32;
+pub use crate::nifpga_sys::{Address, FifoAddress, RegisterAddress};
 
 pub trait RegisterInterface<T: Default + Copy> {
     fn read(&self, address: RegisterAddress) -> Result<T>;
+    /// Reads the register and returns the status directly instead of a [`Result`].
+    ///
+    /// This is intended for tight polling loops where you want to check the status
+    /// occasionally rather than on every read. The value is only valid if the status is not an error.
+    ///
+    /// The default is built on [`RegisterInterface::read`]. Backends which get a status from the driver
+    /// should override it to also return warnings with the value.
+    fn read_raw(&self, address: RegisterAddress) -> (T, NiFpgaStatus) {
+        match self.read(address) {
+            Ok(value) => (value, NiFpgaStatus::SUCCESS),
+            Err(FPGAError::InternalError(status)) => (T::default(), status),
+            Err(FPGAError::AlreadyRunning) => (T::default(), NiFpgaStatus::ALREADY_RUNNING),
+            Err(_) => (T::default(), NiFpgaStatus::SOFTWARE_FAULT),
+        }
+    }
     fn write(&self, address: RegisterAddress, data: T) -> Result<()>;
+    /// Reads the array register onto the stack.
+    ///
+    /// See [`RegisterInterface::read_array_mut`] for the alignment and length requirements.
     fn read_array<const N: usize>(&self, address: RegisterAddress) -> Result<[T; N]> {
         let mut array: [T; N] = [T::default(); N];
         self.read_array_mut(address, &mut array)?;
         Ok(array)
     }
+    /// Reads the array register into the provided array.
+    ///
+    /// # Safety
+    ///
+    /// This is safe to call. Implementations pass the array to the driver as a raw pointer and length
+    /// so they rely on two things which the Rust types already guarantee:
+    ///
+    /// * The array is aligned for `T`, as any Rust array or slice of `T` is. The driver writes whole
+    ///   elements through the pointer so it must never be cast from a buffer of a smaller type.
+    /// * The length passed is the length of the array so the driver never writes past the end of it.
+    ///
+    /// The register address must also be one generated for an array of `T`. The driver reports
+    /// a misaligned address as an error rather than accessing memory.
     fn read_array_mut<const N: usize>(
         &self,
         address: RegisterAddress,
         array: &mut [T; N],
     ) -> Result<()>;
+    /// Reads the array register into a slice whose length is only known at runtime.
+    ///
+    /// The slice must be the full length of the array register.
+    /// The same alignment and length requirements apply as [`RegisterInterface::read_array_mut`].
+    /// This is used for arrays too large to read onto the stack with [`RegisterInterface::read_array`].
+    ///
+    /// The default can't be built on [`RegisterInterface::read_array_mut`] as that needs the length at
+    /// compile time, so it returns a [`NiFpgaStatus::FEATURE_NOT_SUPPORTED`] error and existing backends
+    /// don't need to implement it. The [`Session`] implements it with a single driver call so
+    /// [`DynArrayRegister`](crate::registers::DynArrayRegister) works with a real FPGA. Other backends
+    /// should override it to support [`DynArrayRegister`](crate::registers::DynArrayRegister).
+    fn read_array_slice(&self, _address: RegisterAddress, _array: &mut [T]) -> Result<()> {
+        Err(NiFpgaStatus::FEATURE_NOT_SUPPORTED.into())
+    }
+    /// Writes the array to the array register.
+    ///
+    /// The driver only reads through the pointer but the same alignment and length requirements
+    /// apply as [`RegisterInterface::read_array_mut`].
     fn write_array<const N: usize>(&self, address: RegisterAddress, data: &[T; N]) -> Result<()>;
+
+    /// Reads the registers in the order of the addresses provided.
+    ///
+    /// Each register is read with a separate call, one after the other. For the session these are
+    /// calls into the driver which are opaque to the compiler so they can't be reordered relative to
+    /// each other and the reads reach the FPGA in this order. No fence is needed for this.
+    ///
+    /// This stops at the first error.
+    fn read_sequence(&self, addresses: &[RegisterAddress]) -> Result<Vec<T>> {
+        addresses
+            .iter()
+            .map(|&address| self.read(address))
+            .collect()
+    }
 }
 
 /// The read region is created by calling [`FifoInterface::read_no_copy`] on the FIFO interface.
@@ -39,12 +110,14 @@ pub trait RegisterInterface<T: Default + Copy> {
 /// This returns this structure where you can use elements to read the data from the FIFO.
 ///
 /// When this structure is dropped the elements are released back to the FIFO automatically.
+#[cfg(feature = "std")]
 pub struct FifoReadRegion<'session, 'data, T: NativeFpgaType> {
     session: &'session Session,
     fifo: FifoAddress,
     pub elements: &'data [T],
 }
 
+#[cfg(feature = "std")]
 impl<'s, 'd, T: NativeFpgaType> Drop for FifoReadRegion<'s, 'd, T> {
     fn drop(&mut self) {
         // Cant return result from drop so ignore it.
@@ -59,12 +132,14 @@ impl<'s, 'd, T: NativeFpgaType> Drop for FifoReadRegion<'s, 'd, T> {
 /// This returns this structure where you can use elements to write the data to the FIFO as a mutable slice.
 ///
 /// When this structure is dropped the elements are released back to the FIFO automatically.
+#[cfg(feature = "std")]
 pub struct FifoWriteRegion<'session, 'data, T: NativeFpgaType> {
     session: &'session Session,
     fifo: FifoAddress,
     pub elements: &'data mut [T],
 }
 
+#[cfg(feature = "std")]
 impl<'s, 'd, T: NativeFpgaType> Drop for FifoWriteRegion<'s, 'd, T> {
     fn drop(&mut self) {
         // Cant return result from drop so ignore it.
@@ -74,6 +149,8 @@ impl<'s, 'd, T: NativeFpgaType> Drop for FifoWriteRegion<'s, 'd, T> {
     }
 }
 
+/// Access to the DMA FIFOs for a given type.
+#[cfg(feature = "std")]
 pub trait FifoInterface<T: NativeFpgaType> {
     /// Reads the elements into the provided buffer up to the size of the buffer.
     ///
@@ -106,21 +183,94 @@ pub trait FifoInterface<T: NativeFpgaType> {
         elements: usize,
         timeout: Option<Duration>,
     ) -> Result<(FifoWriteRegion<T>, usize)>;
+
+    /// The host buffer depth last configured for the FIFO through this backend, if known.
+    ///
+    /// [`Session`] records the depth returned by [`FifoControlInterface::configure_fifo`].
+    /// The default returns [`None`] for backends which don't track it.
+    fn configured_depth(&self, _fifo: FifoAddress) -> Option<usize> {
+        None
+    }
+}
+
+/// Control of the DMA FIFOs which doesn't depend on the data type.
+///
+/// This is implemented by [`Session`] but can be implemented by other backends, e.g. for testing.
+#[cfg(feature = "std")]
+pub trait FifoControlInterface {
+    /// Specify the depth of the host memory part of the FIFO.
+    ///
+    /// Returns the actual size configured which may be larger than the request.
+    fn configure_fifo(&self, fifo: FifoAddress, requested_depth: usize) -> Result<usize>;
+    /// Start the FIFO.
+    fn start_fifo(&self, fifo: FifoAddress) -> Result<()>;
+    /// Stop the FIFO.
+    fn stop_fifo(&self, fifo: FifoAddress) -> Result<()>;
+    /// Gets the endpoint number of a peer-to-peer FIFO.
+    fn get_peer_to_peer_fifo_endpoint(&self, fifo: FifoAddress) -> Result<PeerToPeerEndpoint>;
+}
+
+/// Checks the pointer the driver returned for a zero-copy region can be used as a slice.
+///
+/// A misaligned pointer would make the slice undefined behaviour so we catch it in debug builds.
+/// The driver may return a null pointer when no elements were acquired, e.g. on a timeout.
+#[cfg(feature = "std")]
+#[inline]
+fn debug_assert_region<T>(data: *const T, elements: usize) {
+    debug_assert!(
+        elements == 0 || (!data.is_null() && data as usize & (core::mem::align_of::<T>() - 1) == 0),
+        "The FPGA driver returned a region at {data:p} which is not aligned to {} bytes.",
+        core::mem::align_of::<T>()
+    );
+}
+
+/// Create the slice for a zero-copy read region from the driver's pointer.
+///
+/// # Safety
+///
+/// If `elements` is not 0 the pointer must point to `elements` values which stay valid for `'a`.
+#[cfg(feature = "std")]
+unsafe fn read_region_slice<'a, T>(data: *const T, elements: usize) -> &'a [T] {
+    debug_assert_region(data, elements);
+    if elements == 0 {
+        &[]
+    } else {
+        core::slice::from_raw_parts(data, elements)
+    }
+}
+
+/// Create the slice for a zero-copy write region from the driver's pointer.
+///
+/// # Safety
+///
+/// If `elements` is not 0 the pointer must point to `elements` values which stay valid
+/// and unaliased for `'a`.
+#[cfg(feature = "std")]
+unsafe fn write_region_slice<'a, T>(data: *mut T, elements: usize) -> &'a mut [T] {
+    debug_assert_region(data, elements);
+    if elements == 0 {
+        &mut []
+    } else {
+        core::slice::from_raw_parts_mut(data, elements)
+    }
 }
 
 /// First entry is the rust type, second is the text used for that type in the FPGA interface.
+#[cfg(feature = "std")]
 macro_rules! impl_type_session_interface {
     ($rust_type:ty, $fpga_type:literal) => {
 
 
         paste! {
-            impl NativeFpgaType for $rust_type {}
-
             impl RegisterInterface<$rust_type> for Session {
                 fn read(&self, address: RegisterAddress) -> Result<$rust_type> {
+                    let (value, return_code) = self.read_raw(address);
+                    to_fpga_result(value, return_code)
+                }
+                fn read_raw(&self, address: RegisterAddress) -> ($rust_type, NiFpgaStatus) {
                     let mut value: $rust_type = $rust_type::default();
                     let return_code = unsafe {[< NiFpga_Read $fpga_type >](self.handle, address, &mut value)};
-                    to_fpga_result(value, return_code)
+                    (value, return_code)
                 }
                 fn write(&self, address: RegisterAddress, value: $rust_type) -> Result<()> {
                     let return_code = unsafe {[< NiFpga_Write $fpga_type >](self.handle, address, value)};
@@ -130,6 +280,10 @@ macro_rules! impl_type_session_interface {
                     let return_code = unsafe {[< NiFpga_ReadArray $fpga_type >](self.handle, address, array.as_mut_ptr(), N)};
                     to_fpga_result((), return_code)
                 }
+                fn read_array_slice(&self, address: RegisterAddress, array: &mut [$rust_type]) -> Result<()> {
+                    let return_code = unsafe {[< NiFpga_ReadArray $fpga_type >](self.handle, address, array.as_mut_ptr(), array.len())};
+                    to_fpga_result((), return_code)
+                }
                 fn write_array<const N:usize>(&self, address: RegisterAddress, value: &[$rust_type;N]) -> Result<()> {
                     let return_code = unsafe {[< NiFpga_WriteArray $fpga_type >](self.handle, address, value.as_ptr(), N)};
                     to_fpga_result((), return_code)
@@ -137,45 +291,129 @@ macro_rules! impl_type_session_interface {
             }
 
             impl FifoInterface<$rust_type> for Session {
-                fn read_fifo(&self, fifo: u32, data: &mut [$rust_type], timeout: Option<Duration>) -> Result< usize> {
+                fn read_fifo(&self, fifo: FifoAddress, data: &mut [$rust_type], timeout: Option<Duration>) -> Result< usize> {
                     let mut elements_remaining: size_t = 0;
                     let return_code = unsafe {[< NiFpga_ReadFifo $fpga_type >](self.handle, fifo, data.as_mut_ptr(), data.len(), timeout.into(), &mut elements_remaining)};
                     to_fpga_result(elements_remaining, return_code)
                 }
-                fn write_fifo(&self, fifo: u32, data: &[$rust_type], timeout: Option<Duration>) -> Result<usize> {
+                fn write_fifo(&self, fifo: FifoAddress, data: &[$rust_type], timeout: Option<Duration>) -> Result<usize> {
                     let mut elements_remaining: size_t = 0;
                     let return_code = unsafe {[< NiFpga_WriteFifo $fpga_type >](self.handle, fifo, data.as_ptr(), data.len(), timeout.into(), &mut elements_remaining)};
                     to_fpga_result(elements_remaining, return_code)
                 }
-                fn zero_copy_read(&self, fifo: u32, elements: usize, timeout: Option<Duration>) -> Result<(FifoReadRegion<$rust_type>, usize)> {
+                fn zero_copy_read(&self, fifo: FifoAddress, elements: usize, timeout: Option<Duration>) -> Result<(FifoReadRegion<$rust_type>, usize)> {
                     let mut elements_acquired: size_t = 0;
                     let mut elements_remaining: size_t = 0;
-                    let mut data: *const $rust_type = std::ptr::null();
+                    let mut data: *const $rust_type = core::ptr::null();
                     let return_code = unsafe {[< NiFpga_AcquireFifoReadElements $fpga_type >](self.handle, fifo, &mut data, elements, timeout.into(), &mut elements_acquired, &mut elements_remaining)};
-                    let read_region = FifoReadRegion{session: self, fifo, elements: unsafe {std::slice::from_raw_parts(data, elements_acquired)}};
+                    let read_region = FifoReadRegion{session: self, fifo, elements: unsafe {read_region_slice(data, elements_acquired)}};
                     to_fpga_result((read_region, elements_remaining), return_code)
                 }
-                fn zero_copy_write(&self, fifo: u32, elements: usize, timeout: Option<Duration>) -> Result<(FifoWriteRegion<$rust_type>, usize)> {
+                fn zero_copy_write(&self, fifo: FifoAddress, elements: usize, timeout: Option<Duration>) -> Result<(FifoWriteRegion<$rust_type>, usize)> {
                     let mut elements_acquired: size_t = 0;
                     let mut elements_remaining: size_t = 0;
-                    let mut data: *mut $rust_type = std::ptr::null_mut();
+                    let mut data: *mut $rust_type = core::ptr::null_mut();
                     let return_code = unsafe {[< NiFpga_AcquireFifoWriteElements $fpga_type >](self.handle, fifo, &mut data, elements, timeout.into(), &mut elements_acquired, &mut elements_remaining)};
-                    let write_region = FifoWriteRegion{session: self, fifo, elements: unsafe {std::slice::from_raw_parts_mut(data, elements_acquired)}};
+                    let write_region = FifoWriteRegion{session: self, fifo, elements: unsafe {write_region_slice(data, elements_acquired)}};
                     to_fpga_result((write_region, elements_remaining), return_code)
                 }
+                fn configured_depth(&self, fifo: FifoAddress) -> Option<usize> {
+                    self.configured_fifo_depth(fifo)
+                }
             }
         }
     }
 }
 
-impl_type_session_interface!(u8, "U8");
-impl_type_session_interface!(u16, "U16");
-impl_type_session_interface!(u32, "U32");
-impl_type_session_interface!(u64, "U64");
-impl_type_session_interface!(i8, "I8");
-impl_type_session_interface!(i16, "I16");
-impl_type_session_interface!(i32, "I32");
-impl_type_session_interface!(i64, "I64");
-impl_type_session_interface!(f32, "Sgl");
-impl_type_session_interface!(f64, "Dbl");
-impl_type_session_interface!(FpgaBool, "Bool");
+impl NativeFpgaType for u8 {}
+impl NativeFpgaType for u16 {}
+impl NativeFpgaType for u32 {}
+impl NativeFpgaType for u64 {}
+impl NativeFpgaType for i8 {}
+impl NativeFpgaType for i16 {}
+impl NativeFpgaType for i32 {}
+impl NativeFpgaType for i64 {}
+impl NativeFpgaType for f32 {}
+impl NativeFpgaType for f64 {}
+impl NativeFpgaType for FpgaBool {}
+
+#[cfg(feature = "std")]
+mod session_impls {
+    use super::*;
+
+    impl_type_session_interface!(u8, "U8");
+    impl_type_session_interface!(u16, "U16");
+    impl_type_session_interface!(u32, "U32");
+    impl_type_session_interface!(u64, "U64");
+    impl_type_session_interface!(i8, "I8");
+    impl_type_session_interface!(i16, "I16");
+    impl_type_session_interface!(i32, "I32");
+    impl_type_session_interface!(i64, "I64");
+    impl_type_session_interface!(f32, "Sgl");
+    impl_type_session_interface!(f64, "Dbl");
+    impl_type_session_interface!(FpgaBool, "Bool");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockSession;
+
+    const MISSING_REGISTER: RegisterAddress = 0xBAD;
+
+    fn registers() -> MockSession {
+        MockSession::new()
+            .with_register(0x10, 0x20)
+            .with_register(0x20, 0x40)
+            .with_register(0x30, 0x60)
+    }
+
+    #[test]
+    fn test_default_read_raw_returns_value_with_success() {
+        let registers = registers();
+        assert_eq!(
+            RegisterInterface::<u32>::read_raw(&registers, 0x10),
+            (0x20, NiFpgaStatus::SUCCESS)
+        );
+    }
+
+    #[test]
+    fn test_default_read_raw_returns_driver_status() {
+        let registers = registers();
+        assert_eq!(
+            RegisterInterface::<u32>::read_raw(&registers, MISSING_REGISTER),
+            (0, NiFpgaStatus::RESOURCE_NOT_FOUND)
+        );
+    }
+
+    #[test]
+    fn test_read_sequence_reads_in_order() {
+        let registers = registers();
+        let values =
+            RegisterInterface::<u32>::read_sequence(&registers, &[0x30, 0x10, 0x20]).unwrap();
+        assert_eq!(values, vec![0x60, 0x20, 0x40]);
+        assert_eq!(*registers.reads.borrow(), vec![0x30, 0x10, 0x20]);
+    }
+
+    #[test]
+    fn test_empty_region_allows_null_pointer() {
+        let elements = unsafe { read_region_slice::<u64>(core::ptr::null(), 0) };
+        assert!(elements.is_empty());
+    }
+
+    #[test]
+    fn test_aligned_region_is_used_as_slice() {
+        let mut buffer = [1u64, 2, 3];
+        let elements = unsafe { write_region_slice(buffer.as_mut_ptr(), 3) };
+        assert_eq!(elements, &[1, 2, 3]);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "is not aligned")]
+    fn test_misaligned_region_fails_check() {
+        let buffer = [0u64; 4];
+        let misaligned = (buffer.as_ptr() as *const u8).wrapping_add(1) as *const u64;
+        let _ = unsafe { read_region_slice(misaligned, 2) };
+    }
+}
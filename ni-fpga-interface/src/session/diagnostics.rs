@@ -0,0 +1,153 @@
+//! Collects the state of the session for support and bug reports.
+
+use std::fmt::Display;
+
+use super::{FifoAddress, Session};
+use crate::error::FPGAError;
+
+/// The parts of a generated FPGA interface needed to build a [`Diagnostics`] report.
+///
+/// The build crate generates an `Interface` type implementing this for each bitfile.
+pub trait FpgaInterface {
+    /// The signature of the bitfile the interface was generated from.
+    fn signature(&self) -> &'static str;
+
+    /// The name and address of each FIFO in the interface.
+    fn fifos(&self) -> &'static [(&'static str, FifoAddress)];
+
+    /// Read every indicator in the interface.
+    ///
+    /// Each value is returned with its name, formatted with [`Debug`](std::fmt::Debug).
+    fn read_indicators(&self, session: &Session) -> Result<Vec<(&'static str, String)>, FPGAError>;
+}
+
+/// A snapshot of the session configuration and state.
+///
+/// The [`Display`] output is intended to be attached to bug reports. This is the supported way
+/// to serialize the report as the crate doesn't depend on serde.
+///
+/// The NI FPGA C API doesn't provide a way to query the VI run state and IRQ contexts aren't
+/// tracked by the session, so neither is included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostics {
+    pub bitfile: String,
+    pub signature: String,
+    /// The signature the interface was generated from.
+    ///
+    /// If this doesn't match [`Diagnostics::signature`] the bindings are out of date.
+    pub interface_signature: String,
+    pub resource: String,
+    /// Whether the FPGA will be reset when the session closes.
+    pub reset_on_close: bool,
+    /// The host buffer depth of each FIFO, if it has been configured through this session.
+    pub fifo_depths: Vec<(String, Option<usize>)>,
+    /// The current value of each indicator.
+    pub indicators: Vec<(String, String)>,
+}
+
+impl Session {
+    /// Gather the details of this session for diagnostics.
+    ///
+    /// This reads every indicator in the interface so will fail if any read fails.
+    ///
+    /// ```no_run
+    /// use ni_fpga_interface::session::{NiFpgaContext, Session};
+    ///
+    ///#  mod fpga_defs {
+    ///#      pub struct Interface;
+    ///#      impl ni_fpga_interface::session::diagnostics::FpgaInterface for Interface {
+    ///#          fn signature(&self) -> &'static str { "" }
+    ///#          fn fifos(&self) -> &'static [(&'static str, u32)] { &[] }
+    ///#          fn read_indicators(&self, _: &ni_fpga_interface::session::Session)
+    ///#              -> Result<Vec<(&'static str, String)>, ni_fpga_interface::error::FPGAError> {
+    ///#              Ok(Vec::new())
+    ///#          }
+    ///#      }
+    ///#  }
+    ///#  let fpga_context = NiFpgaContext::new().unwrap();
+    ///#  let session = Session::new(
+    ///#   &fpga_context,
+    ///#  "./NiFpga_Main.lvbitx",
+    ///# "signature",
+    ///# "RIO0",
+    ///# (),
+    ///# ).unwrap();
+    /// println!("{}", session.diagnostics(&fpga_defs::Interface).unwrap());
+    /// ```
+    pub fn diagnostics(&self, iface: &dyn FpgaInterface) -> Result<Diagnostics, FPGAError> {
+        let fifo_depths = iface
+            .fifos()
+            .iter()
+            .map(|&(name, fifo)| (name.to_owned(), self.configured_fifo_depth(fifo)))
+            .collect();
+        let indicators = iface
+            .read_indicators(self)?
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect();
+        Ok(Diagnostics {
+            bitfile: self.bitfile.clone(),
+            signature: self.signature.clone(),
+            interface_signature: iface.signature().to_owned(),
+            resource: self.resource.clone(),
+            reset_on_close: self.close_attribute & 1 == 0,
+            fifo_depths,
+            indicators,
+        })
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Bitfile: {}", self.bitfile)?;
+        writeln!(f, "Signature: {}", self.signature)?;
+        writeln!(f, "Interface Signature: {}", self.interface_signature)?;
+        writeln!(f, "Resource: {}", self.resource)?;
+        writeln!(f, "Reset On Close: {}", self.reset_on_close)?;
+        writeln!(f, "FIFO Depths:")?;
+        for (name, depth) in &self.fifo_depths {
+            match depth {
+                Some(depth) => writeln!(f, "  {name}: {depth}")?,
+                None => writeln!(f, "  {name}: Not Configured")?,
+            }
+        }
+        writeln!(f, "Indicators:")?;
+        for (name, value) in &self.indicators {
+            writeln!(f, "  {name}: {value}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Diagnostics;
+
+    #[test]
+    fn test_diagnostics_display() {
+        let diagnostics = Diagnostics {
+            bitfile: "NiFpga_Main.lvbitx".to_owned(),
+            signature: "A0613989B20F45FC6E79EB71383493E8".to_owned(),
+            interface_signature: "A0613989B20F45FC6E79EB71383493E8".to_owned(),
+            resource: "RIO0".to_owned(),
+            reset_on_close: true,
+            fifo_depths: vec![
+                ("NumbersToFPGA".to_owned(), Some(1024)),
+                ("NumbersFromFPGA".to_owned(), None),
+            ],
+            indicators: vec![("U8Result".to_owned(), "4".to_owned())],
+        };
+
+        let expected = "Bitfile: NiFpga_Main.lvbitx\n\
+            Signature: A0613989B20F45FC6E79EB71383493E8\n\
+            Interface Signature: A0613989B20F45FC6E79EB71383493E8\n\
+            Resource: RIO0\n\
+            Reset On Close: true\n\
+            FIFO Depths:\n  \
+            NumbersToFPGA: 1024\n  \
+            NumbersFromFPGA: Not Configured\n\
+            Indicators:\n  \
+            U8Result: 4\n";
+        assert_eq!(diagnostics.to_string(), expected);
+    }
+}
@@ -6,12 +6,17 @@ use crate::error::{to_fpga_result, Result};
 use crate::nifpga_sys::*;
 use libc::size_t;
 
-use super::Session;
+use super::{FifoControlInterface, Session};
 
 impl Session {
     /// Specify the depth of the host memory part of the FIFO.
     ///
     /// Returns the actual size configured which may be larger than the request.
+    /// The session records it for [`Session::configured_fifo_depth`].
+    ///
+    /// With an older C API built with `ApiVersion::Legacy` the actual size isn't available.
+    /// The requested size is returned instead and nothing is recorded, so checks which need
+    /// the configured depth such as [`crate::fifos::ReadFifo::buffer_was_full`] are unavailable.
     pub fn configure_fifo(&self, fifo: FifoAddress, requested_depth: usize) -> Result<usize> {
         let mut actual_depth: size_t = 0;
         let result = unsafe {
@@ -22,7 +27,13 @@ impl Session {
                 &mut actual_depth as *mut size_t,
             )
         };
-        to_fpga_result(actual_depth, result)
+        let depth = to_fpga_result(actual_depth, result)?;
+        // The legacy shim reports zero as it can't find out the actual depth.
+        if depth == 0 {
+            return Ok(requested_depth);
+        }
+        self.record_fifo_depth(fifo, depth);
+        Ok(depth)
     }
 
     /// Start the FIFO.
@@ -59,3 +70,21 @@ impl Session {
         to_fpga_result(endpoint, result)
     }
 }
+
+impl FifoControlInterface for Session {
+    fn configure_fifo(&self, fifo: FifoAddress, requested_depth: usize) -> Result<usize> {
+        Session::configure_fifo(self, fifo, requested_depth)
+    }
+
+    fn start_fifo(&self, fifo: FifoAddress) -> Result<()> {
+        Session::start_fifo(self, fifo)
+    }
+
+    fn stop_fifo(&self, fifo: FifoAddress) -> Result<()> {
+        Session::stop_fifo(self, fifo)
+    }
+
+    fn get_peer_to_peer_fifo_endpoint(&self, fifo: FifoAddress) -> Result<PeerToPeerEndpoint> {
+        Session::get_peer_to_peer_fifo_endpoint(self, fifo)
+    }
+}
@@ -1,17 +1,29 @@
 //! Holds session management functions for the FPGA.
 //!
 mod data_interfaces;
+#[cfg(feature = "std")]
+pub mod diagnostics;
+#[cfg(feature = "std")]
 pub mod fifo_control;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
 use std::sync::atomic::AtomicBool;
+#[cfg(feature = "std")]
 use std::sync::atomic::Ordering;
-use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::{Arc, Mutex, PoisonError};
 
+#[cfg(feature = "std")]
 use crate::error::{to_fpga_result, FPGAError};
+#[cfg(feature = "std")]
 use crate::nifpga_sys::*;
 pub use data_interfaces::*;
 
+#[cfg(feature = "std")]
 static CONTEXT_ACTIVE: AtomicBool = AtomicBool::new(false);
 
+#[cfg(feature = "std")]
 /// An NI FPGA context must be initialized and active for the FPGA
 /// functions to work.
 ///
@@ -19,6 +31,7 @@ static CONTEXT_ACTIVE: AtomicBool = AtomicBool::new(false);
 /// and automatically destruct when no more sessions are active or it is out of scope.
 pub struct NiFpgaContext();
 
+#[cfg(feature = "std")]
 impl NiFpgaContext {
     /// Create a new NI FPGA context which is required to open a session.
     ///
@@ -39,6 +52,19 @@ impl NiFpgaContext {
     }
 }
 
+#[cfg(feature = "std")]
+impl NiFpgaContext {
+    /// Finalize the library and return the status rather than ignoring it as drop does.
+    fn finalize(self) -> Result<(), FPGAError> {
+        let status = unsafe { NiFpga_Finalize() };
+        CONTEXT_ACTIVE.store(false, Ordering::SeqCst);
+        // We have already done what drop would do.
+        std::mem::forget(self);
+        to_fpga_result((), status)
+    }
+}
+
+#[cfg(feature = "std")]
 impl Drop for NiFpgaContext {
     fn drop(&mut self) {
         unsafe {
@@ -49,6 +75,19 @@ impl Drop for NiFpgaContext {
 }
 
 /// Options for the session.
+///
+/// The fields are public but you can also use the builder style setters.
+/// As these are `const` you can define the options for your application in one place.
+///
+/// ```
+/// use ni_fpga_interface::session::SessionOptions;
+///
+/// const APP_OPTIONS: SessionOptions = SessionOptions::new().reset_on_close(false);
+/// ```
+///
+/// Anything which converts into these can be passed to [`Session::new`] so `()`
+/// can be used for the defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SessionOptions {
     /// Reset the FPGA on close (default: True)
     pub reset_on_close: bool,
@@ -57,6 +96,27 @@ pub struct SessionOptions {
 }
 
 impl SessionOptions {
+    /// Create the default options to run on open and reset on close.
+    pub const fn new() -> Self {
+        Self {
+            reset_on_close: true,
+            run_on_open: true,
+        }
+    }
+
+    /// Set whether the FPGA is reset when the session is closed.
+    pub const fn reset_on_close(mut self, reset_on_close: bool) -> Self {
+        self.reset_on_close = reset_on_close;
+        self
+    }
+
+    /// Set whether the FPGA is run when the session is opened.
+    pub const fn run_on_open(mut self, run_on_open: bool) -> Self {
+        self.run_on_open = run_on_open;
+        self
+    }
+
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
     fn open_attribute(&self) -> u32 {
         let mut attribute = 0;
         if !self.run_on_open {
@@ -65,6 +125,7 @@ impl SessionOptions {
         attribute
     }
 
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
     fn close_attribute(&self) -> u32 {
         let mut attribute = 0;
         if !self.reset_on_close {
@@ -76,19 +137,40 @@ impl SessionOptions {
 
 impl Default for SessionOptions {
     fn default() -> Self {
-        Self {
-            reset_on_close: true,
-            run_on_open: true,
-        }
+        Self::new()
+    }
+}
+
+impl From<()> for SessionOptions {
+    fn from(_: ()) -> Self {
+        Self::default()
+    }
+}
+
+impl From<&SessionOptions> for SessionOptions {
+    fn from(options: &SessionOptions) -> Self {
+        *options
     }
 }
 
+#[cfg(feature = "std")]
 pub struct Session {
     pub handle: SessionHandle,
     close_attribute: u32,
+    bitfile: String,
+    signature: String,
+    resource: String,
+    /// The host buffer depths configured through this session.
+    fifo_depths: Mutex<HashMap<FifoAddress, usize>>,
+    /// The IRQs reported as asserted which haven't been acknowledged, for the debug build warning.
+    #[cfg(debug_assertions)]
+    pub(crate) pending_irqs: crate::irq::PendingIrqs,
+    /// Set when the session is closed explicitly so drop doesn't close it again.
+    closed: bool,
     _context: Arc<NiFpgaContext>,
 }
 
+#[cfg(feature = "std")]
 impl Session {
     /// Create a new session for the specified bitfile and resource.
     ///
@@ -97,7 +179,7 @@ impl Session {
     /// The bitfile is the compiled FPGA VI and can be provided as a relative or absolute path.
     ///
     /// The session options specify run and reset behaviour for the session.
-    /// You can use default to run on start and reset on end.
+    /// You can use `()` to run on start and reset on end or pass [`SessionOptions`].
     ///
     /// # Example
     /// ```no_run
@@ -109,7 +191,7 @@ impl Session {
     ///   "./NiFpga_Main.lvbitx",
     ///  "signature",
     /// "RIO0",
-    /// &Default::default(),
+    /// (),
     /// ).unwrap();
     /// ```
     pub fn new(
@@ -117,17 +199,18 @@ impl Session {
         bitfile: &str,
         signature: &str,
         resource: &str,
-        options: &SessionOptions,
+        options: impl Into<SessionOptions>,
     ) -> Result<Self, crate::error::FPGAError> {
+        let options = options.into();
         let mut handle: SessionHandle = 0;
-        let bitfile = std::ffi::CString::new(bitfile).unwrap();
-        let signature = std::ffi::CString::new(signature).unwrap();
-        let resource = std::ffi::CString::new(resource).unwrap();
+        let bitfile_c = std::ffi::CString::new(bitfile).unwrap();
+        let signature_c = std::ffi::CString::new(signature).unwrap();
+        let resource_c = std::ffi::CString::new(resource).unwrap();
         let result = unsafe {
             NiFpga_Open(
-                bitfile.as_ptr(),
-                signature.as_ptr(),
-                resource.as_ptr(),
+                bitfile_c.as_ptr(),
+                signature_c.as_ptr(),
+                resource_c.as_ptr(),
                 options.open_attribute(),
                 &mut handle,
             )
@@ -137,6 +220,13 @@ impl Session {
                 handle,
                 _context: context.clone(),
                 close_attribute: options.close_attribute(),
+                bitfile: bitfile.to_owned(),
+                signature: signature.to_owned(),
+                resource: resource.to_owned(),
+                fifo_depths: Mutex::new(HashMap::new()),
+                #[cfg(debug_assertions)]
+                pending_irqs: Default::default(),
+                closed: false,
             },
             result,
         )
@@ -154,13 +244,13 @@ impl Session {
     ///#  "./NiFpga_Main.lvbitx",
     ///# "signature",
     ///# "RIO0",
-    ///# &Default::default(),
+    ///# (),
     ///# ).unwrap();
     ///
-    /// session.set_options(&SessionOptions { reset_on_close: false, ..Default::default() });
+    /// session.set_options(SessionOptions::new().reset_on_close(false));
     /// ```
-    pub fn set_options(&mut self, options: &SessionOptions) {
-        self.close_attribute = options.close_attribute();
+    pub fn set_options(&mut self, options: impl Into<SessionOptions>) {
+        self.close_attribute = options.into().close_attribute();
     }
 
     /// Reset the FPGA back to it's initial state.
@@ -180,6 +270,17 @@ impl Session {
         to_fpga_result((), result)
     }
 
+    /// Runs the FPGA on the target, treating an FPGA which is already running as success.
+    ///
+    /// This is useful when you can't be sure whether the FPGA was run when the session was opened.
+    /// If the FPGA is already running and `wait_until_done` is true this returns immediately.
+    pub fn run_idempotent(&mut self, wait_until_done: bool) -> Result<(), crate::error::FPGAError> {
+        match self.run(wait_until_done) {
+            Err(FPGAError::AlreadyRunning) => Ok(()),
+            result => result,
+        }
+    }
+
     /// Abort the FPGA VI.
     pub fn abort(&mut self) -> Result<(), crate::error::FPGAError> {
         let result = unsafe { NiFpga_Abort(self.handle) };
@@ -194,24 +295,160 @@ impl Session {
         to_fpga_result((), result)
     }
 
+    /// The host buffer depth last configured for the FIFO through this session.
+    ///
+    /// This is [`None`] if the FIFO hasn't been configured since the session was opened,
+    /// in which case the driver uses its default depth.
+    pub fn configured_fifo_depth(&self, fifo: FifoAddress) -> Option<usize> {
+        self.fifo_depths
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&fifo)
+            .copied()
+    }
+
+    fn record_fifo_depth(&self, fifo: FifoAddress, depth: usize) {
+        self.fifo_depths
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(fifo, depth);
+    }
+
+    /// Issues a full memory fence.
+    ///
+    /// Calls to the FPGA are opaque to the compiler so they are never reordered relative
+    /// to each other. This is only needed if you also need ordering against other memory
+    /// operations, for example data shared with another thread.
+    ///
+    /// See the [`crate::registers`] module for more details on ordering.
+    pub fn barrier(&self) {
+        std::sync::atomic::fence(Ordering::SeqCst);
+    }
+
     /// Close the session to the FPGA and resets it if set for the session.
-    pub fn close(self) -> Result<(), crate::error::FPGAError> {
-        let result = unsafe { NiFpga_Close(self.handle, self.close_attribute) };
+    pub fn close(mut self) -> Result<(), crate::error::FPGAError> {
+        self.close_handle()
+    }
+
+    /// Close the session and finalize the context it was opened with.
+    ///
+    /// This gives a deterministic shutdown for applications which don't want to rely on drop order.
+    /// Pass in your reference to the context so this can hold the last one once the session is closed.
+    /// If other sessions or clones of the context are still alive the context is left active,
+    /// to be finalized when the last of them is dropped, and [`FPGAError::ContextInUse`] is returned.
+    ///
+    /// If closing fails the context is still finalized and the close error is returned.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use ni_fpga_interface::session::{NiFpgaContext, Session};
+    ///
+    /// let fpga_context = NiFpgaContext::new().unwrap();
+    /// let session = Session::new(&fpga_context, "./NiFpga_Main.lvbitx", "signature", "RIO0", ()).unwrap();
+    /// session.close_and_finalize(fpga_context).unwrap();
+    /// ```
+    pub fn close_and_finalize(
+        mut self,
+        context: Arc<NiFpgaContext>,
+    ) -> Result<(), crate::error::FPGAError> {
+        let result = self.close_handle();
+        // Drop the session's reference so the caller's is the last one if no other sessions are open.
+        drop(self);
+        let finalize_result = match Arc::into_inner(context) {
+            Some(context) => context.finalize(),
+            None => Err(FPGAError::ContextInUse),
+        };
+        result.and(finalize_result)
+    }
 
+    /// Close the session handle and mark it closed so drop doesn't close it again.
+    fn close_handle(&mut self) -> Result<(), crate::error::FPGAError> {
+        self.closed = true;
+        let result = unsafe { NiFpga_Close(self.handle, self.close_attribute) };
         to_fpga_result((), result)
     }
 }
 
+#[cfg(feature = "std")]
 impl Drop for Session {
     fn drop(&mut self) {
-        unsafe {
-            NiFpga_Close(self.handle, self.close_attribute);
+        if !self.closed {
+            unsafe {
+                NiFpga_Close(self.handle, self.close_attribute);
+            }
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "std")]
+    use super::*;
+    #[cfg(feature = "std")]
+    use std::sync::atomic::AtomicUsize;
+
+    /// The number of times the test stand-in for the driver has been finalized.
+    #[cfg(feature = "std")]
+    static FINALIZE_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    // The driver isn't linked into the tests so stand in for the calls made on close.
+    #[cfg(feature = "std")]
+    #[no_mangle]
+    extern "C" fn NiFpga_Close(
+        _session: SessionHandle,
+        _attribute: u32,
+    ) -> crate::error::NiFpgaStatus {
+        crate::error::NiFpgaStatus::SUCCESS
+    }
+
+    #[cfg(feature = "std")]
+    #[no_mangle]
+    extern "C" fn NiFpga_Finalize() -> crate::error::NiFpgaStatus {
+        FINALIZE_CALLS.fetch_add(1, Ordering::SeqCst);
+        crate::error::NiFpgaStatus::SUCCESS
+    }
+
+    /// A session which hasn't been opened with the driver.
+    #[cfg(feature = "std")]
+    fn test_session(context: &Arc<NiFpgaContext>) -> Session {
+        Session {
+            handle: 0,
+            close_attribute: 0,
+            bitfile: String::new(),
+            signature: String::new(),
+            resource: String::new(),
+            fifo_depths: Mutex::new(HashMap::new()),
+            #[cfg(debug_assertions)]
+            pending_irqs: Default::default(),
+            closed: false,
+            _context: context.clone(),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_close_and_finalize_with_last_context() {
+        let context = Arc::new(NiFpgaContext());
+        let session = test_session(&context);
+        let finalized_before = FINALIZE_CALLS.load(Ordering::SeqCst);
+
+        session.close_and_finalize(context).unwrap();
+
+        assert!(FINALIZE_CALLS.load(Ordering::SeqCst) > finalized_before);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_close_and_finalize_reports_context_in_use() {
+        let context = Arc::new(NiFpgaContext());
+        let other_session = test_session(&context);
+        let session = test_session(&context);
+
+        let result = session.close_and_finalize(context);
+
+        assert!(matches!(result, Err(FPGAError::ContextInUse)));
+        drop(other_session);
+    }
 
     #[test]
     fn test_session_options_default() {
@@ -247,4 +484,19 @@ mod tests {
         options.reset_on_close = false;
         assert_eq!(options.close_attribute(), 1);
     }
+
+    #[test]
+    fn test_session_options_builder() {
+        let options = super::SessionOptions::new()
+            .run_on_open(false)
+            .reset_on_close(false);
+        assert!(!options.run_on_open);
+        assert!(!options.reset_on_close);
+    }
+
+    #[test]
+    fn test_session_options_from_unit_is_default() {
+        let options: super::SessionOptions = ().into();
+        assert_eq!(options, super::SessionOptions::default());
+    }
 }
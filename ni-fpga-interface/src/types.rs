@@ -1,6 +1,7 @@
-/// Wrapper for any types shared across the interface.
-///
-use std::time::Duration;
+//! Wrapper for any types shared across the interface.
+//!
+//! These only depend on `core` so they can be shared with `no_std` code.
+use core::time::Duration;
 
 /// The representation of a boolean in the FPGA interface.
 #[repr(transparent)]
@@ -112,6 +113,16 @@ impl IrqSelection {
     pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
         (0..32).filter(|irq| self.is_irq_set(*irq))
     }
+
+    /// The IRQs set in either selection.
+    pub const fn union(self, other: IrqSelection) -> IrqSelection {
+        IrqSelection(self.0 | other.0)
+    }
+
+    /// The IRQs in this selection which aren't in the other.
+    pub const fn without(self, other: IrqSelection) -> IrqSelection {
+        IrqSelection(self.0 & !other.0)
+    }
 }
 
 impl Default for IrqSelection {
@@ -120,8 +131,8 @@ impl Default for IrqSelection {
     }
 }
 
-impl std::fmt::Debug for IrqSelection {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Debug for IrqSelection {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "IrqSelection[")?;
         let mut first = true;
         for irq in self.iter() {
@@ -219,4 +230,11 @@ mod tests {
         selection.add_irq(2);
         assert_eq!(format!("{:?}", selection), "IrqSelection[0, 2]");
     }
+
+    #[test]
+    fn test_union_and_without() {
+        let selection = IrqSelection::new(0).union(IrqSelection::new(2));
+        assert_eq!(selection.0, 0b101);
+        assert_eq!(selection.without(IrqSelection::new(0)).0, 0b100);
+    }
 }